@@ -0,0 +1,56 @@
+/*
+Atomic file replacement
+
+Data is written to <path>.tmp, synced to disk and then renamed to the final
+name. Readers never see a partially written file, and after a power loss
+either the old or the new content is present. Used for all files the host
+keeps: run files, configuration updates and receiver state.
+*/
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// Parent folder is created if missing
+pub fn write(path: &Path, data: &[u8]) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+
+    // Make rename itself persistent
+    File::open(parent)?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_replace() {
+        let dir = std::env::temp_dir().join(format!("gnss-mgr-atomic-{}", std::process::id()));
+        let path = dir.join("sub").join("file.txt");
+
+        write(&path, b"first").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        write(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+
+        // No temporary file left behind
+        assert_eq!(fs::read_dir(dir.join("sub")).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+/*
+Host system clock, set from receiver time
+
+Receiver time is taken from UBX-NAV-TIMEUTC or UBX-NAV-PVT. The system clock
+is sampled when the message is received, so the offset includes the output
+latency of the receiver (typically well below 100 ms).
+
+The system clock is accessed through the SystemClock trait, tests use a
+simulated clock and don't need root privileges.
+*/
+
+use std::io;
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+use crate::ubxlib::frame::{UbxFrame, UbxFrameDeSerialize};
+use crate::ubxlib::ubx_nav_pvt::UbxNavPvt;
+use crate::ubxlib::ubx_nav_timeutc::UbxNavTimeUtc;
+
+pub trait SystemClock {
+    fn now(&self) -> DateTime<Utc>;
+    fn set(&mut self, time: DateTime<Utc>) -> Result<(), String>;
+}
+
+// Linux CLOCK_REALTIME, setting requires CAP_SYS_TIME
+pub struct RealtimeClock {}
+
+impl SystemClock for RealtimeClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn set(&mut self, time: DateTime<Utc>) -> Result<(), String> {
+        let ts = libc::timespec {
+            tv_sec: time.timestamp() as libc::time_t,
+            tv_nsec: time.timestamp_subsec_nanos() as libc::c_long,
+        };
+        let res = unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &ts) };
+        if res < 0 {
+            return Err(format!(
+                "can't set system clock ({})",
+                io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Receiver time and system clock time when it was received
+#[derive(Debug, PartialEq)]
+pub struct TimeSample {
+    pub utc: DateTime<Utc>,
+    pub acc_ns: u32,
+    pub received: DateTime<Utc>,
+}
+
+impl TimeSample {
+    // Decodes UBX-NAV-TIMEUTC or UBX-NAV-PVT, time must be fully resolved
+    pub fn from_frame(frame: &UbxFrame, received: DateTime<Utc>) -> Option<Self> {
+        let mut timeutc = UbxNavTimeUtc::create();
+        let mut pvt = UbxNavPvt::create();
+        let (utc, acc_ns) = if frame.cid == timeutc.cid {
+            timeutc.from_bin(&frame.data);
+            (timeutc.data.utc()?, timeutc.data.t_acc)
+        } else if frame.cid == pvt.cid {
+            pvt.from_bin(&frame.data);
+            if !pvt.data.time_resolved() {
+                return None;
+            }
+            (pvt.data.utc()?, pvt.data.t_acc)
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            utc,
+            acc_ns,
+            received,
+        })
+    }
+
+    // Accuracy limit in ms, compared in u64 as it exceeds u32 in ns
+    pub fn accurate(&self, acc_ms: u32) -> bool {
+        self.acc_ns as u64 <= acc_ms as u64 * 1_000_000
+    }
+
+    // Positive if system clock is behind receiver time
+    pub fn offset(&self) -> Duration {
+        self.utc - self.received
+    }
+}
+
+// Corrects clock by offset of sample, returns applied offset
+pub fn adjust(clock: &mut dyn SystemClock, sample: &TimeSample) -> Result<Duration, String> {
+    let offset = sample.offset();
+    let now = clock.now();
+    clock.set(now + offset)?;
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameSerialize;
+
+    // Simulated clock, advances 10 ms per read
+    struct TestClock {
+        time: std::cell::Cell<DateTime<Utc>>,
+    }
+
+    impl TestClock {
+        fn new(time: DateTime<Utc>) -> Self {
+            Self {
+                time: std::cell::Cell::new(time),
+            }
+        }
+    }
+
+    impl SystemClock for TestClock {
+        fn now(&self) -> DateTime<Utc> {
+            let now = self.time.get();
+            self.time.set(now + Duration::milliseconds(10));
+            now
+        }
+
+        fn set(&mut self, time: DateTime<Utc>) -> Result<(), String> {
+            self.time.set(time);
+            Ok(())
+        }
+    }
+
+    fn timeutc_frame(valid: u8) -> UbxFrame {
+        let mut msg = UbxNavTimeUtc::create();
+        msg.data.t_acc = 25;
+        msg.data.nano = 500_000_000;
+        msg.data.year = 2020;
+        msg.data.month = 11;
+        msg.data.day = 4;
+        msg.data.hour = 13;
+        msg.data.min = 37;
+        msg.data.sec = 42;
+        msg.data.valid = valid;
+        let bin = msg.to_bin();
+        UbxFrame {
+            cid: msg.cid,
+            data: bin[6..bin.len() - 2].to_vec(),
+        }
+    }
+
+    #[test]
+    fn sample_timeutc() {
+        let received = Utc.ymd(2020, 11, 4).and_hms(13, 37, 40);
+        let sample = TimeSample::from_frame(&timeutc_frame(0x07), received).unwrap();
+        assert_eq!(
+            sample.utc,
+            Utc.ymd(2020, 11, 4).and_hms_milli(13, 37, 42, 500)
+        );
+        assert_eq!(sample.acc_ns, 25);
+        assert_eq!(sample.offset(), Duration::milliseconds(2500));
+    }
+
+    #[test]
+    fn accurate() {
+        let mut sample = TimeSample {
+            utc: Utc.ymd(2020, 11, 4).and_hms(13, 37, 42),
+            acc_ns: 100_000_000,
+            received: Utc.ymd(2020, 11, 4).and_hms(13, 37, 40),
+        };
+        assert!(sample.accurate(100));
+        assert!(!sample.accurate(99));
+
+        // Top of time sync accuracy range, beyond u32 in ns
+        sample.acc_ns = u32::MAX;
+        assert!(sample.accurate(60_000));
+        assert!(sample.accurate(4295));
+        assert!(!sample.accurate(4294));
+    }
+
+    #[test]
+    fn sample_unresolved() {
+        let received = Utc.ymd(2020, 11, 4).and_hms(13, 37, 40);
+        assert!(TimeSample::from_frame(&timeutc_frame(0x05), received).is_none());
+    }
+
+    #[test]
+    fn sample_pvt() {
+        let mut msg = UbxNavPvt::create();
+        msg.data.year = 2020;
+        msg.data.month = 11;
+        msg.data.day = 4;
+        msg.data.valid = 0x03; // date and time valid, not fully resolved
+        let bin = msg.to_bin();
+        let mut frame = UbxFrame {
+            cid: msg.cid,
+            data: bin[6..bin.len() - 2].to_vec(),
+        };
+        let received = Utc.ymd(2020, 11, 4).and_hms(0, 0, 0);
+        assert!(TimeSample::from_frame(&frame, received).is_none());
+
+        frame.data[11] = 0x07;
+        let sample = TimeSample::from_frame(&frame, received).unwrap();
+        assert_eq!(sample.offset(), Duration::zero());
+    }
+
+    #[test]
+    fn adjust_clock() {
+        let start = Utc.ymd(2000, 1, 1).and_hms(0, 0, 0);
+        let mut clock = TestClock::new(start);
+        let sample = TimeSample {
+            utc: Utc.ymd(2020, 11, 4).and_hms(13, 37, 42),
+            acc_ns: 25,
+            received: clock.now(),
+        };
+
+        let offset = adjust(&mut clock, &sample).unwrap();
+        assert_eq!(offset, Utc.ymd(2020, 11, 4).and_hms(13, 37, 42) - start);
+
+        // Time elapsed since receipt is kept
+        assert_eq!(
+            clock.now(),
+            Utc.ymd(2020, 11, 4).and_hms_milli(13, 37, 42, 10)
+        );
+    }
+}
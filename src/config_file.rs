@@ -1,16 +1,32 @@
 use ini::{ini::Properties, Ini};
 use log::info;
 use std::collections::HashSet;
+use std::fs;
 use std::path::Path;
 
+use crate::atomic_file;
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::ubx_cfg_msg::message_cid;
+use crate::ubxlib::ubx_cfg_prt::{protocols_from_str, PortId, BAUDRATES, PROTO_UBX};
+use crate::ubxlib::ubx_cfg_tmode2::TimeModeType;
+use crate::ubxlib::ubx_cfg_tp5::{TimeGrid, TimePulse};
+
 #[derive(Debug, Default)]
 pub struct GnssMgrConfig {
     pub update_rate: Option<i32>,
     pub mode: Option<String>,
+    pub hnr_rate: Option<i32>,
     pub systems: Option<Vec<String>>,
+    pub navx5: Option<Navx5>,
     pub imu_angles: Option<Angles>,
+    pub auto_align: Option<bool>,
     pub vrp2antenna: Option<Xyz>,
     pub vrp2imu: Option<Xyz>,
+    pub wheel_ticks: Option<WheelTicks>,
+    pub messages: Option<Vec<MessageRate>>,
+    pub ports: Option<Vec<PortConfig>>,
+    pub timepulses: Option<Vec<TimePulseConfig>>,
+    pub time_mode: Option<TimeMode>,
 }
 
 impl GnssMgrConfig {
@@ -42,23 +58,61 @@ impl GnssMgrConfig {
         };
 
         // Update rate
-        self.update_rate = Self::get_int(sec_general, "update-rate", |val| val >= 1 && val <= 2);
+        self.update_rate = Self::get_int(sec_general, "update-rate", |val| (1..=2).contains(&val));
 
         // GNSS operation mode
-        let valid_modes = vec!["stationary", "vehicle"];
+        let valid_modes = ["stationary", "vehicle"];
         self.mode = Self::get_string(sec_navigation, "mode", |val| valid_modes.contains(&val));
 
+        // High navigation rate output of fused solution
+        self.hnr_rate = Self::get_int(sec_navigation, "hnr-rate", |val| (1..=30).contains(&val));
+
         // Satellite systems
         let value_str = Self::get_string(sec_navigation, "systems", |_| true);
-        self.systems = match value_str {
-            Some(x) => Some(x.split(';').map(|s| s.to_string().to_lowercase()).collect()),
-            _ => None,
+        self.systems =
+            value_str.map(|x| x.split(';').map(|s| s.to_string().to_lowercase()).collect());
+
+        // Navigation expert settings, each setting is optional
+        let is_bool = |x: &str| x == "true" || x == "false";
+        let mut navx5 = Navx5 {
+            aop: Self::get_string(sec_navigation, "aop", is_bool).map(|x| x == "true"),
+            aop_orbit_max_error: Self::get_int(sec_navigation, "aop-orbit-max-error", |val| {
+                (5..=1000).contains(&val)
+            })
+            .map(|val| val as u16),
+            min_svs: Self::get_int(sec_navigation, "min-svs", |val| (1..=32).contains(&val))
+                .map(|val| val as u8),
+            max_svs: Self::get_int(sec_navigation, "max-svs", |val| (1..=32).contains(&val))
+                .map(|val| val as u8),
+            min_cno: Self::get_int(sec_navigation, "min-cno", |val| (0..=50).contains(&val))
+                .map(|val| val as u8),
+            ini_fix_3d: Self::get_string(sec_navigation, "ini-fix-3d", is_bool)
+                .map(|x| x == "true"),
+            wkn_rollover: Self::get_int(sec_navigation, "wkn-rollover", |val| {
+                (1024..=65535).contains(&val)
+            })
+            .map(|val| val as u16),
+            ppp: Self::get_string(sec_navigation, "ppp", is_bool).map(|x| x == "true"),
         };
+        if let (Some(min), Some(max)) = (navx5.min_svs, navx5.max_svs) {
+            if min > max {
+                info!("min-svs {} larger than max-svs {}, ignoring both", min, max);
+                navx5.min_svs = None;
+                navx5.max_svs = None;
+            }
+        }
+        if navx5 != Navx5::default() {
+            self.navx5 = Some(navx5);
+        }
 
-        // IMU Angles
-        let imu_yaw = Self::get_int(sec_installation, "yaw", |val| val >= 0 && val <= 360);
-        let imu_pitch = Self::get_int(sec_installation, "pitch", |val| val >= -90 && val <= 90);
-        let imu_roll = Self::get_int(sec_installation, "roll", |val| val >= -180 && val <= 180);
+        // IMU Angles, resolution 0.01°
+        let imu_yaw = Self::get_float(sec_installation, "yaw", |val| (0.0..=360.0).contains(&val));
+        let imu_pitch = Self::get_float(sec_installation, "pitch", |val| {
+            (-90.0..=90.0).contains(&val)
+        });
+        let imu_roll = Self::get_float(sec_installation, "roll", |val| {
+            (-180.0..=180.0).contains(&val)
+        });
         self.imu_angles = match (imu_yaw, imu_pitch, imu_roll) {
             (Some(imu_yaw), Some(imu_pitch), Some(imu_roll)) => {
                 Angles::new(imu_yaw, imu_pitch, imu_roll)
@@ -66,9 +120,15 @@ impl GnssMgrConfig {
             _ => None,
         };
 
+        // Automatic IMU mount alignment
+        let value_str = Self::get_string(sec_installation, "auto-align", |x| {
+            x == "true" || x == "false"
+        });
+        self.auto_align = value_str.map(|x| x == "true");
+
         // Lever Arms
         let value_str = Self::get_string(sec_installation, "vrp2antenna", |x| {
-            Xyz::from_str(&x).is_some()
+            Xyz::from_str(x).is_some()
         });
         self.vrp2antenna = match value_str {
             Some(x) => Xyz::from_str(&x),
@@ -76,15 +136,131 @@ impl GnssMgrConfig {
         };
 
         let value_str =
-            Self::get_string(sec_installation, "vrp2imu", |x| Xyz::from_str(&x).is_some());
+            Self::get_string(sec_installation, "vrp2imu", |x| Xyz::from_str(x).is_some());
         self.vrp2imu = match value_str {
             Some(x) => Xyz::from_str(&x),
             _ => None,
         };
 
+        // Wheel tick sensor, each setting is optional
+        let wt = WheelTicks {
+            factor: Self::get_float(sec_installation, "wt-factor", |val| {
+                val > 0.0 && val <= 4294.0
+            }),
+            quant_error: Self::get_float(sec_installation, "wt-quant-error", |val| {
+                (0.0..=4294.0).contains(&val)
+            }),
+            count_max: Self::get_string(sec_installation, "wt-count-max", |x| {
+                x.parse::<u32>().is_ok()
+            })
+            .map(|x| x.parse::<u32>().unwrap()),
+            latency: Self::get_int(sec_installation, "wt-latency", |val| {
+                (0..=65535).contains(&val)
+            })
+            .map(|val| val as u16),
+            dir_inverted: Self::get_string(sec_installation, "wt-dir-polarity", |x| {
+                x == "normal" || x == "inverted"
+            })
+            .map(|x| x == "inverted"),
+            use_speed: Self::get_string(sec_installation, "wt-source", |x| {
+                x == "ticks" || x == "speed"
+            })
+            .map(|x| x == "speed"),
+        };
+        if wt != WheelTicks::default() {
+            self.wheel_ticks = Some(wt);
+        }
+
+        // Message output rates, section is optional
+        if let Some(sec_messages) = conf.section(Some("messages")) {
+            self.messages = Self::get_messages(sec_messages);
+        }
+
+        // Port settings, section is optional
+        if let Some(sec_ports) = conf.section(Some("ports")) {
+            self.ports = Self::get_ports(sec_ports);
+        }
+
+        // Time pulse settings, section is optional
+        if let Some(sec_timepulse) = conf.section(Some("timepulse")) {
+            self.timepulses = Self::get_timepulses(sec_timepulse);
+        }
+
+        // Time mode of stationary timing receivers, section is optional
+        if let Some(sec_timemode) = conf.section(Some("timemode")) {
+            self.time_mode = Self::get_time_mode(sec_timemode);
+        }
+
         Ok(())
     }
 
+    /*
+    Updates keys of a section in a config file, comments and layout are preserved.
+    - Existing keys get the new value
+    - Missing keys are added after the last key of the section
+    */
+    pub fn update_keys<P: AsRef<Path>>(
+        path: P,
+        section: &str,
+        values: &[(&str, String)],
+    ) -> Result<(), String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|_err| "configuration file not found")?;
+
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        let mut in_section = false;
+        let mut found = false;
+        let mut last_key_line = None;
+        let mut missing: Vec<&(&str, String)> = values.iter().collect();
+
+        for (index, line) in lines.iter_mut().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                in_section = trimmed == format!("[{}]", section);
+                found |= in_section;
+                if in_section {
+                    last_key_line = Some(index);
+                }
+                continue;
+            }
+
+            if !in_section || trimmed.starts_with('#') || trimmed.starts_with(';') {
+                continue;
+            }
+
+            if let Some(pos) = trimmed.find('=') {
+                last_key_line = Some(index);
+                let key = trimmed[..pos].trim();
+                if let Some(i) = missing.iter().position(|(k, _)| *k == key) {
+                    *line = format!("{}={}", key, missing[i].1);
+                    missing.remove(i);
+                }
+            }
+        }
+
+        if !found {
+            return Err(format!("section [{}] not found", section));
+        }
+
+        // Add keys not present in file at end of section
+        let insert_at = last_key_line.unwrap() + 1;
+        for (offset, (key, value)) in missing.iter().enumerate() {
+            lines.insert(insert_at + offset, format!("{}={}", key, value));
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+
+        // Config is never left half written
+        atomic_file::write(path, text.as_bytes()).map_err(|err| {
+            format!(
+                "can't write configuration file {} ({})",
+                path.display(),
+                err
+            )
+        })
+    }
+
     fn have_duplicates(conf: &Ini) -> Result<(), String> {
         let general_section_name = "general";
         let mut keys = HashSet::<String>::new();
@@ -104,6 +280,339 @@ impl GnssMgrConfig {
         Ok(())
     }
 
+    fn get_messages(section: &Properties) -> Option<Vec<MessageRate>> {
+        let mut messages = Vec::new();
+
+        for (name, _) in section.iter() {
+            let cid = match message_cid(name) {
+                Some(cid) => cid,
+                None => {
+                    info!("unknown message {}, ignoring", name);
+                    continue;
+                }
+            };
+
+            if let Some(rate) = Self::get_int(section, name, |val| (0..=255).contains(&val)) {
+                messages.push(MessageRate {
+                    name: name.to_uppercase(),
+                    cid,
+                    rate: rate as u8,
+                });
+            }
+        }
+
+        if messages.is_empty() {
+            None
+        } else {
+            Some(messages)
+        }
+    }
+
+    /*
+    Port settings given on the command line, e.g. "usb-out=ubx;nmea"
+
+    Settings use the keys of the [ports] section. Unlike in the config file,
+    an invalid setting is an error.
+    */
+    pub fn ports_from_args(args: &[&str]) -> Result<Option<Vec<PortConfig>>, String> {
+        let mut ports: Vec<PortConfig> = Vec::new();
+
+        for arg in args {
+            let mut conf = Ini::new();
+            let (key, value) = match arg.find('=') {
+                Some(pos) => (&arg[..pos], &arg[pos + 1..]),
+                None => return Err(format!("invalid port setting {}", arg)),
+            };
+            conf.with_section(Some("ports")).set(key, value);
+
+            // unwrap can't fail, section was just created
+            let cfg = match Self::get_ports(conf.section(Some("ports")).unwrap()) {
+                Some(cfg) => cfg[0],
+                None => return Err(format!("invalid port setting {}", arg)),
+            };
+
+            match ports.iter_mut().find(|p| p.port == cfg.port) {
+                Some(p) => {
+                    p.in_proto_mask = cfg.in_proto_mask.or(p.in_proto_mask);
+                    p.out_proto_mask = cfg.out_proto_mask.or(p.out_proto_mask);
+                    p.baudrate = cfg.baudrate.or(p.baudrate);
+                }
+                None => ports.push(cfg),
+            }
+        }
+
+        if ports.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(ports))
+        }
+    }
+
+    fn get_ports(section: &Properties) -> Option<Vec<PortConfig>> {
+        let mut ports: Vec<PortConfig> = Vec::new();
+
+        // Keys are of form <port>-<setting>, e.g. usb-out
+        for (key, _) in section.iter() {
+            let (port_name, setting) = match key.rfind('-') {
+                Some(pos) => (&key[..pos], &key[pos + 1..]),
+                None => (key, ""),
+            };
+
+            let port = match PortId::from_name(port_name) {
+                Some(port) => port,
+                None => {
+                    info!("unknown port setting {}, ignoring", key);
+                    continue;
+                }
+            };
+
+            let index = match ports.iter().position(|p| p.port == port) {
+                Some(index) => index,
+                None => {
+                    ports.push(PortConfig::new(port));
+                    ports.len() - 1
+                }
+            };
+            let cfg = &mut ports[index];
+
+            match setting {
+                "in" | "out" => {
+                    // UBX must remain enabled on UART1, otherwise the receiver can't be managed
+                    let value = Self::get_string(section, key, |x| match protocols_from_str(x) {
+                        Some(mask) => port != PortId::Uart1 || mask & PROTO_UBX != 0,
+                        None => false,
+                    });
+                    let mask = value.and_then(|x| protocols_from_str(&x));
+                    if setting == "in" {
+                        cfg.in_proto_mask = mask;
+                    } else {
+                        cfg.out_proto_mask = mask;
+                    }
+                }
+                "baudrate" if port == PortId::Uart2 => {
+                    cfg.baudrate =
+                        Self::get_int(section, key, |val| BAUDRATES.contains(&(val as u32)))
+                            .map(|val| val as u32);
+                }
+                _ => info!("unknown port setting {}, ignoring", key),
+            }
+        }
+
+        ports.retain(|p| {
+            p.in_proto_mask.is_some() || p.out_proto_mask.is_some() || p.baudrate.is_some()
+        });
+        if ports.is_empty() {
+            None
+        } else {
+            Some(ports)
+        }
+    }
+
+    fn get_timepulses(section: &Properties) -> Option<Vec<TimePulseConfig>> {
+        let mut pulses: Vec<TimePulseConfig> = Vec::new();
+        let mut conflicts: Vec<(TimePulse, &str)> = Vec::new();
+
+        // Keys are of form <pulse>-<setting>, e.g. tp1-freq-locked
+        for (key, _) in section.iter() {
+            let (pulse_name, setting) = match key.find('-') {
+                Some(pos) => (&key[..pos], &key[pos + 1..]),
+                None => (key, ""),
+            };
+
+            let pulse = match TimePulse::from_name(pulse_name) {
+                Some(pulse) => pulse,
+                None => {
+                    info!("unknown time pulse setting {}, ignoring", key);
+                    continue;
+                }
+            };
+
+            let index = match pulses.iter().position(|p| p.pulse == pulse) {
+                Some(index) => index,
+                None => {
+                    pulses.push(TimePulseConfig::new(pulse));
+                    pulses.len() - 1
+                }
+            };
+            let cfg = &mut pulses[index];
+            let locked = setting.ends_with("-locked");
+            let is_bool = |x: &str| x == "true" || x == "false";
+
+            match setting {
+                "active" => {
+                    cfg.active = Self::get_string(section, key, is_bool).map(|x| x == "true");
+                }
+                "sync-gnss" => {
+                    cfg.sync_gnss = Self::get_string(section, key, is_bool).map(|x| x == "true");
+                }
+                "freq" | "freq-locked" | "period" | "period-locked" => {
+                    // Frequency in Hz or period in us, locked and unlocked must be same kind
+                    let is_freq = setting.starts_with("freq");
+                    let value = if is_freq {
+                        Self::get_int(section, key, |val| (1..=10_000_000).contains(&val))
+                            .map(|val| val as u32)
+                    } else {
+                        Self::get_string(
+                            section,
+                            key,
+                            |x| matches!(x.parse::<u32>(), Ok(v) if v > 0),
+                        )
+                        .map(|x| x.parse::<u32>().unwrap())
+                    };
+                    if let Some(value) = value {
+                        if cfg.is_freq == Some(!is_freq) {
+                            conflicts.push((pulse, "freq"));
+                        }
+                        cfg.is_freq = Some(is_freq);
+                        if locked {
+                            cfg.freq_period_lock = Some(value);
+                        } else {
+                            cfg.freq_period = Some(value);
+                        }
+                    }
+                }
+                "length" | "length-locked" | "duty" | "duty-locked" => {
+                    // Pulse length in us or duty cycle in %, locked and unlocked must be same kind
+                    let is_length = setting.starts_with("length");
+                    let value = if is_length {
+                        Self::get_string(section, key, |x| x.parse::<u32>().is_ok())
+                            .map(|x| x.parse::<u32>().unwrap())
+                    } else {
+                        Self::get_float(section, key, |val| (0.0..=100.0).contains(&val)).map(
+                            |val| {
+                                (val as f64 / 100.0 * 4_294_967_296.0).min(u32::MAX as f64) as u32
+                            },
+                        )
+                    };
+                    if let Some(value) = value {
+                        if cfg.is_length == Some(!is_length) {
+                            conflicts.push((pulse, "length"));
+                        }
+                        cfg.is_length = Some(is_length);
+                        if locked {
+                            cfg.pulse_len_ratio_lock = Some(value);
+                        } else {
+                            cfg.pulse_len_ratio = Some(value);
+                        }
+                    }
+                }
+                "cable-delay" | "rf-delay" => {
+                    let value = Self::get_int(section, key, |val| {
+                        (i16::MIN as i32..=i16::MAX as i32).contains(&val)
+                    })
+                    .map(|val| val as i16);
+                    if setting == "cable-delay" {
+                        cfg.cable_delay = value;
+                    } else {
+                        cfg.rf_delay = value;
+                    }
+                }
+                "user-delay" => {
+                    cfg.user_delay = Self::get_int(section, key, |_| true);
+                }
+                "polarity" => {
+                    cfg.rising =
+                        Self::get_string(section, key, |x| x == "rising" || x == "falling")
+                            .map(|x| x == "rising");
+                }
+                "align" => {
+                    cfg.grid =
+                        Self::get_string(section, key, |x| x == "utc" || x == "gps").map(|x| {
+                            if x == "gps" {
+                                TimeGrid::Gps
+                            } else {
+                                TimeGrid::Utc
+                            }
+                        });
+                }
+                _ => info!("unknown time pulse setting {}, ignoring", key),
+            }
+        }
+
+        // Unit flags apply to locked and unlocked value, if only one is given the
+        // receiver would reinterpret the other one in the new unit
+        for cfg in &pulses {
+            if cfg.is_freq.is_some()
+                && (cfg.freq_period.is_none() || cfg.freq_period_lock.is_none())
+            {
+                conflicts.push((cfg.pulse, "freq"));
+            }
+            if cfg.is_length.is_some()
+                && (cfg.pulse_len_ratio.is_none() || cfg.pulse_len_ratio_lock.is_none())
+            {
+                conflicts.push((cfg.pulse, "length"));
+            }
+        }
+
+        // Mixed or incomplete frequency/period or length/duty cycle settings can't be applied
+        for (pulse, kind) in conflicts {
+            info!(
+                "{:?} {} needs locked and unlocked value in same unit, ignoring",
+                pulse, kind
+            );
+            let cfg = pulses.iter_mut().find(|p| p.pulse == pulse).unwrap();
+            if kind == "freq" {
+                cfg.is_freq = None;
+                cfg.freq_period = None;
+                cfg.freq_period_lock = None;
+            } else {
+                cfg.is_length = None;
+                cfg.pulse_len_ratio = None;
+                cfg.pulse_len_ratio_lock = None;
+            }
+        }
+
+        pulses.retain(|p| *p != TimePulseConfig::new(p.pulse));
+        if pulses.is_empty() {
+            None
+        } else {
+            Some(pulses)
+        }
+    }
+
+    fn get_time_mode(section: &Properties) -> Option<TimeMode> {
+        let mut tm = TimeMode {
+            mode: Self::get_string(section, "mode", |x| TimeModeType::from_name(x).is_some())
+                .and_then(|x| TimeModeType::from_name(&x)),
+            svin_min_dur: Self::get_int(section, "svin-min-dur", |val| val > 0)
+                .map(|val| val as u32),
+            svin_acc_limit: Self::get_float(section, "svin-acc-limit", |val| {
+                val > 0.0 && val <= 1000.0
+            })
+            .map(|val| (val * 1000.0).round() as u32),
+            fixed_pos: None,
+            fixed_pos_acc: Self::get_float(section, "fixed-pos-acc", |val| {
+                val > 0.0 && val <= 1000.0
+            })
+            .map(|val| (val * 1000.0).round() as u32),
+        };
+
+        let ecef = Self::get_string(section, "ecef", |x| {
+            FixedPosition::ecef_from_str(x).is_some()
+        })
+        .and_then(|x| FixedPosition::ecef_from_str(&x));
+        let llh = Self::get_string(section, "llh", |x| FixedPosition::llh_from_str(x).is_some())
+            .and_then(|x| FixedPosition::llh_from_str(&x));
+        tm.fixed_pos = match (ecef, llh) {
+            (Some(_), Some(_)) => {
+                info!("both ecef and llh position specified, ignoring");
+                None
+            }
+            (ecef, llh) => ecef.or(llh),
+        };
+
+        if tm.mode == Some(TimeModeType::Fixed) && tm.fixed_pos.is_none() {
+            info!("fixed time mode requires a position, ignoring");
+            tm.mode = None;
+        }
+
+        if tm != TimeMode::default() {
+            Some(tm)
+        } else {
+            None
+        }
+    }
+
     fn get_int<F>(section: &Properties, keyname: &str, fn_check: F) -> Option<i32>
     where
         F: FnOnce(i32) -> bool,
@@ -132,6 +641,32 @@ impl GnssMgrConfig {
         value
     }
 
+    fn get_float<F>(section: &Properties, keyname: &str, fn_check: F) -> Option<f32>
+    where
+        F: FnOnce(f32) -> bool,
+    {
+        match section.get(keyname) {
+            Some("") => {
+                info!("no value for {} specified, ignoring", keyname);
+                None
+            }
+            Some(val_str) => match Xyz::parse_float(val_str) {
+                Ok(value) if fn_check(value) => {
+                    info!("{}: {}", keyname, val_str);
+                    Some(value)
+                }
+                Ok(_) | Err(_) => {
+                    info!("invalid value {} for key {}", val_str, keyname);
+                    None
+                }
+            },
+            _ => {
+                info!("key '{}' not defined", keyname);
+                None
+            }
+        }
+    }
+
     fn get_string<F>(section: &Properties, keyname: &str, fn_check: F) -> Option<String>
     where
         F: FnOnce(&str) -> bool,
@@ -141,7 +676,7 @@ impl GnssMgrConfig {
                 info!("no value for {} specified, ignoring", keyname);
                 None
             }
-            Some(value) if fn_check(&value) => {
+            Some(value) if fn_check(value) => {
                 info!("{}: {}", keyname, value);
                 Some(String::from(value))
             }
@@ -160,14 +695,168 @@ impl GnssMgrConfig {
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Angles {
-    pub yaw: i32,
-    pub pitch: i32,
-    pub roll: i32,
+    pub yaw: f32,   // degrees, 0..360
+    pub pitch: f32, // degrees, -90..90
+    pub roll: f32,  // degrees, -180..180
 }
 
 impl Angles {
-    pub fn new(yaw: i32, pitch: i32, roll: i32) -> Option<Self> {
-        Some(Self { yaw, pitch, roll })
+    // Angles are rounded to receiver resolution of 0.01°
+    pub fn new(yaw: f32, pitch: f32, roll: f32) -> Option<Self> {
+        let round = |v: f32| (v * 100.0).round() / 100.0;
+        Some(Self {
+            yaw: round(yaw),
+            pitch: round(pitch),
+            roll: round(roll),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WheelTicks {
+    pub factor: Option<f32>,        // m per tick
+    pub quant_error: Option<f32>,   // m
+    pub count_max: Option<u32>,     // tick counter wraps at this value
+    pub latency: Option<u16>,       // ms
+    pub dir_inverted: Option<bool>, // direction pin polarity
+    pub use_speed: Option<bool>,    // use speed instead of ticks
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Navx5 {
+    pub aop: Option<bool>,                // AssistNow Autonomous
+    pub aop_orbit_max_error: Option<u16>, // m
+    pub min_svs: Option<u8>,
+    pub max_svs: Option<u8>,
+    pub min_cno: Option<u8>, // dBHz
+    pub ini_fix_3d: Option<bool>,
+    pub wkn_rollover: Option<u16>, // GPS week number
+    pub ppp: Option<bool>,         // precise point positioning, PPP firmwares only
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageRate {
+    pub name: String,
+    pub cid: UbxCID,
+    pub rate: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PortConfig {
+    pub port: PortId,
+    pub in_proto_mask: Option<u16>,
+    pub out_proto_mask: Option<u16>,
+    pub baudrate: Option<u32>,
+}
+
+impl PortConfig {
+    pub fn new(port: PortId) -> Self {
+        Self {
+            port,
+            in_proto_mask: None,
+            out_proto_mask: None,
+            baudrate: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimePulseConfig {
+    pub pulse: TimePulse,
+    pub active: Option<bool>,
+    pub is_freq: Option<bool>,    // frequency in Hz instead of period in us
+    pub freq_period: Option<u32>, // not locked to GNSS
+    pub freq_period_lock: Option<u32>, // locked to GNSS
+    pub is_length: Option<bool>,  // length in us instead of duty cycle in 2^-32
+    pub pulse_len_ratio: Option<u32>,
+    pub pulse_len_ratio_lock: Option<u32>,
+    pub cable_delay: Option<i16>, // ns
+    pub rf_delay: Option<i16>,    // ns
+    pub user_delay: Option<i32>,  // ns
+    pub rising: Option<bool>,     // polarity
+    pub grid: Option<TimeGrid>,   // pulse aligned to top of second of UTC or GPS
+    pub sync_gnss: Option<bool>,  // synchronize to GNSS time when locked
+}
+
+impl TimePulseConfig {
+    pub fn new(pulse: TimePulse) -> Self {
+        Self {
+            pulse,
+            active: None,
+            is_freq: None,
+            freq_period: None,
+            freq_period_lock: None,
+            is_length: None,
+            pulse_len_ratio: None,
+            pulse_len_ratio_lock: None,
+            cable_delay: None,
+            rf_delay: None,
+            user_delay: None,
+            rising: None,
+            grid: None,
+            sync_gnss: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TimeMode {
+    pub mode: Option<TimeModeType>,
+    pub svin_min_dur: Option<u32>,   // s
+    pub svin_acc_limit: Option<u32>, // mm
+    pub fixed_pos: Option<FixedPosition>,
+    pub fixed_pos_acc: Option<u32>, // mm
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixedPosition {
+    Ecef { x: i32, y: i32, z: i32 },         // cm
+    Llh { lat: i32, lon: i32, height: i32 }, // 1e-7°, 1e-7°, cm
+}
+
+impl FixedPosition {
+    // ECEF coordinates in m, format x;y;z
+    pub fn ecef_from_str(text: &str) -> Option<Self> {
+        let (x, y, z) = Self::parse_triple(text)?;
+        let valid = |v: f64| v.abs() <= 10_000_000.0;
+        if !(valid(x) && valid(y) && valid(z)) {
+            return None;
+        }
+        let cm = |v: f64| (v * 100.0).round() as i32;
+        Some(FixedPosition::Ecef {
+            x: cm(x),
+            y: cm(y),
+            z: cm(z),
+        })
+    }
+
+    // Latitude and longitude in degrees, height above ellipsoid in m, format lat;lon;height
+    pub fn llh_from_str(text: &str) -> Option<Self> {
+        let (lat, lon, height) = Self::parse_triple(text)?;
+        if !((-90.0..=90.0).contains(&lat)
+            && (-180.0..=180.0).contains(&lon)
+            && (-1000.0..=20_000.0).contains(&height))
+        {
+            return None;
+        }
+        Some(FixedPosition::Llh {
+            lat: (lat * 1e7).round() as i32,
+            lon: (lon * 1e7).round() as i32,
+            height: (height * 100.0).round() as i32,
+        })
+    }
+
+    // Double precision is required for cm resolution of ECEF coordinates
+    fn parse_triple(text: &str) -> Option<(f64, f64, f64)> {
+        let values: Vec<f64> = text
+            .split(';')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        match values[..] {
+            [a, b, c] => Some((a, b, c)),
+            _ => None,
+        }
     }
 }
 
@@ -259,7 +948,7 @@ mod xyz_reader {
 
     fn float_same(a: f32, b: f32) -> bool {
         let delta = (a - b).abs();
-        return delta < 0.01;
+        delta < 0.01
     }
 }
 
@@ -392,6 +1081,35 @@ mod mode {
     }
 }
 
+#[cfg(test)]
+mod hnr_rate {
+    use super::*;
+
+    #[test]
+    fn key_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_mode_vehicle.conf");
+        assert!(res.is_ok());
+        assert!(config.hnr_rate.is_none());
+    }
+
+    #[test]
+    fn value_ok() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_hnr_rate_ok.conf");
+        assert!(res.is_ok());
+        assert_eq!(config.hnr_rate, Some(20));
+    }
+
+    #[test]
+    fn value_too_high() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_hnr_rate_too_high.conf");
+        assert!(res.is_ok());
+        assert!(config.hnr_rate.is_none());
+    }
+}
+
 #[cfg(test)]
 mod imu_angles {
     use super::*;
@@ -420,9 +1138,335 @@ mod imu_angles {
         assert!(config.imu_angles.is_some());
 
         let angles = config.imu_angles.unwrap();
-        assert_eq!(angles.yaw, 180);
-        assert_eq!(angles.pitch, -90);
-        assert_eq!(angles.roll, 90);
+        assert_eq!(angles.yaw, 180.0);
+        assert_eq!(angles.pitch, -90.0);
+        assert_eq!(angles.roll, 90.0);
+    }
+
+    #[test]
+    fn fraction() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_fraction.conf");
+        assert!(res.is_ok());
+
+        // Values are rounded to 0.01°
+        let angles = config.imu_angles.unwrap();
+        assert!((angles.yaw - 359.99).abs() < 0.001);
+        assert!((angles.pitch - -1.23).abs() < 0.001);
+        assert!((angles.roll - 0.5).abs() < 0.001);
+    }
+}
+
+#[cfg(test)]
+mod auto_align {
+    use super::*;
+
+    #[test]
+    fn key_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_valid.conf");
+        assert!(res.is_ok());
+        assert!(config.auto_align.is_none());
+    }
+
+    #[test]
+    fn enabled() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_auto_align.conf");
+        assert!(res.is_ok());
+        assert_eq!(config.auto_align, Some(true));
+    }
+}
+
+#[cfg(test)]
+mod wheel_ticks {
+    use super::*;
+
+    #[test]
+    fn keys_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_valid.conf");
+        assert!(res.is_ok());
+        assert!(config.wheel_ticks.is_none());
+    }
+
+    #[test]
+    fn ok() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_wheel_ticks_ok.conf");
+        assert!(res.is_ok());
+
+        let wt = config.wheel_ticks.unwrap();
+        assert_eq!(wt.factor, Some(0.025));
+        assert_eq!(wt.quant_error, Some(0.001));
+        assert_eq!(wt.count_max, Some(65535));
+        assert_eq!(wt.latency, Some(15));
+        assert_eq!(wt.dir_inverted, Some(true));
+        assert_eq!(wt.use_speed, Some(false));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_wheel_ticks_invalid.conf");
+        assert!(res.is_ok());
+
+        // Only valid setting remains
+        let wt = config.wheel_ticks.unwrap();
+        assert_eq!(
+            wt,
+            WheelTicks {
+                use_speed: Some(true),
+                ..Default::default()
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod navx5 {
+    use super::*;
+
+    #[test]
+    fn keys_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_valid.conf");
+        assert!(res.is_ok());
+        assert!(config.navx5.is_none());
+    }
+
+    #[test]
+    fn ok() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_navx5_ok.conf");
+        assert!(res.is_ok());
+
+        let navx5 = config.navx5.unwrap();
+        assert_eq!(navx5.aop, Some(true));
+        assert_eq!(navx5.aop_orbit_max_error, Some(50));
+        assert_eq!(navx5.min_svs, Some(4));
+        assert_eq!(navx5.max_svs, Some(24));
+        assert_eq!(navx5.min_cno, Some(10));
+        assert_eq!(navx5.ini_fix_3d, Some(true));
+        assert_eq!(navx5.wkn_rollover, Some(2099));
+        assert_eq!(navx5.ppp, Some(false));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_navx5_invalid.conf");
+        assert!(res.is_ok());
+
+        // Only valid setting remains, min-svs > max-svs drops both
+        let navx5 = config.navx5.unwrap();
+        assert_eq!(
+            navx5,
+            Navx5 {
+                aop: Some(false),
+                ..Default::default()
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod timepulse {
+    use super::*;
+
+    #[test]
+    fn section_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_valid.conf");
+        assert!(res.is_ok());
+        assert!(config.timepulses.is_none());
+    }
+
+    #[test]
+    fn ok() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_timepulse_ok.conf");
+        assert!(res.is_ok());
+
+        let pulses = config.timepulses.unwrap();
+        assert_eq!(pulses.len(), 2);
+
+        let tp1 = pulses[0];
+        assert_eq!(tp1.pulse, TimePulse::Tp1);
+        assert_eq!(tp1.active, Some(true));
+        assert_eq!(tp1.is_freq, Some(false));
+        assert_eq!(tp1.freq_period, Some(1_000_000));
+        assert_eq!(tp1.freq_period_lock, Some(1_000_000));
+        assert_eq!(tp1.is_length, Some(true));
+        assert_eq!(tp1.pulse_len_ratio, Some(0));
+        assert_eq!(tp1.pulse_len_ratio_lock, Some(100_000));
+        assert_eq!(tp1.cable_delay, Some(50));
+        assert_eq!(tp1.rf_delay, Some(0));
+        assert_eq!(tp1.user_delay, Some(-20));
+        assert_eq!(tp1.rising, Some(true));
+        assert_eq!(tp1.grid, Some(TimeGrid::Utc));
+        assert_eq!(tp1.sync_gnss, Some(true));
+
+        let tp2 = pulses[1];
+        assert_eq!(tp2.pulse, TimePulse::Tp2);
+        assert_eq!(tp2.is_freq, Some(true));
+        assert_eq!(tp2.freq_period, Some(1));
+        assert_eq!(tp2.freq_period_lock, Some(10_000_000));
+        assert_eq!(tp2.is_length, Some(false));
+        assert_eq!(tp2.pulse_len_ratio, Some(0));
+        assert_eq!(tp2.pulse_len_ratio_lock, Some(0x8000_0000));
+        assert_eq!(tp2.grid, Some(TimeGrid::Gps));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_timepulse_invalid.conf");
+        assert!(res.is_ok());
+
+        // Mixed frequency and period is dropped, only valid setting remains
+        let pulses = config.timepulses.unwrap();
+        assert_eq!(
+            pulses,
+            vec![TimePulseConfig {
+                rising: Some(false),
+                ..TimePulseConfig::new(TimePulse::Tp1)
+            }]
+        );
+    }
+
+    #[test]
+    fn locked_only() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_timepulse_locked_only.conf");
+        assert!(res.is_ok());
+
+        // Unit change with only one value of a pair is dropped
+        let pulses = config.timepulses.unwrap();
+        assert_eq!(
+            pulses,
+            vec![TimePulseConfig {
+                active: Some(true),
+                ..TimePulseConfig::new(TimePulse::Tp1)
+            }]
+        );
+    }
+}
+
+#[cfg(test)]
+mod timemode {
+    use super::*;
+
+    #[test]
+    fn section_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_imu_valid.conf");
+        assert!(res.is_ok());
+        assert!(config.time_mode.is_none());
+    }
+
+    #[test]
+    fn fixed_ecef() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_timemode_ok.conf");
+        assert!(res.is_ok());
+
+        let tm = config.time_mode.unwrap();
+        assert_eq!(tm.mode, Some(TimeModeType::Fixed));
+        assert_eq!(tm.svin_min_dur, Some(300));
+        assert_eq!(tm.svin_acc_limit, Some(2500));
+        assert_eq!(
+            tm.fixed_pos,
+            Some(FixedPosition::Ecef {
+                x: 412_834_140,
+                y: 78_942_205,
+                z: 459_952_783
+            })
+        );
+        assert_eq!(tm.fixed_pos_acc, Some(1000));
+    }
+
+    #[test]
+    fn position_llh() {
+        assert_eq!(
+            FixedPosition::llh_from_str("47.3456789;8.5123456;480.5"),
+            Some(FixedPosition::Llh {
+                lat: 473_456_789,
+                lon: 85_123_456,
+                height: 48_050
+            })
+        );
+        assert!(FixedPosition::llh_from_str("91.0;8.5;480.5").is_none());
+        assert!(FixedPosition::ecef_from_str("1.0;2.0").is_none());
+        assert!(FixedPosition::ecef_from_str("1.0;2.0;x").is_none());
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_timemode_invalid.conf");
+        assert!(res.is_ok());
+
+        // Fixed mode without valid position is dropped, only valid setting remains
+        let tm = config.time_mode.unwrap();
+        assert_eq!(
+            tm,
+            TimeMode {
+                svin_min_dur: Some(60),
+                ..Default::default()
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod update_keys {
+    use super::*;
+
+    // Tests run in parallel, each one needs its own copy
+    fn copy_of(test: &str, name: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("gnss-mgr-{}-{}-{}", test, std::process::id(), name));
+        fs::copy(format!("test_files/{}", name), &path).unwrap();
+        path
+    }
+
+    #[test]
+    fn update_and_add() {
+        let path = copy_of("update_and_add", "gnss0_imu_valid.conf");
+        let values = [
+            ("yaw", String::from("12.5")),
+            ("pitch", String::from("-3.25")),
+            ("roll", String::from("4")),
+            ("auto-align", String::from("false")),
+        ];
+        let res = GnssMgrConfig::update_keys(&path, "installation", &values);
+        assert!(res.is_ok());
+
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config(&path);
+        assert!(res.is_ok());
+        let angles = config.imu_angles.unwrap();
+        assert_eq!(angles.yaw, 12.5);
+        assert_eq!(angles.pitch, -3.25);
+        assert_eq!(angles.roll, 4.0);
+        assert_eq!(config.auto_align, Some(false));
+
+        // Comments are preserved, new key added to end of section
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("#   roll: value in degrees (-180 to 180)\nyaw=12.5\n"));
+        assert!(text.ends_with("vrp2imu=\nauto-align=false\n"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn section_missing() {
+        let path = copy_of("section_missing", "gnss0_imu_valid.conf");
+        let res = GnssMgrConfig::update_keys(&path, "timepulse", &[("a", String::from("1"))]);
+        assert!(res.is_err());
+
+        fs::remove_file(&path).unwrap();
     }
 }
 
@@ -444,7 +1488,7 @@ mod vrp_antenna {
 
     fn float_same(a: f32, b: f32) -> bool {
         let delta = (a - b).abs();
-        return delta < 0.01;
+        delta < 0.01
     }
 }
 
@@ -467,3 +1511,119 @@ mod sat_systems {
         assert!(!systems.contains(&String::from("glonass")));
     }
 }
+
+#[cfg(test)]
+mod messages {
+    use super::*;
+
+    #[test]
+    fn section_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_mode_vehicle.conf");
+        assert!(res.is_ok());
+        assert!(config.messages.is_none());
+    }
+
+    #[test]
+    fn ok() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_messages_ok.conf");
+        assert!(res.is_ok());
+
+        let messages = config.messages.unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].name, "NMEA-GSV");
+        assert_eq!(messages[0].cid, UbxCID::new(0xF0, 0x03));
+        assert_eq!(messages[0].rate, 0);
+        assert_eq!(messages[1].name, "UBX-NAV-PVT");
+        assert_eq!(messages[1].rate, 1);
+        assert_eq!(messages[2].name, "UBX-ESF-INS");
+        assert_eq!(messages[2].rate, 1);
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_messages_invalid.conf");
+        assert!(res.is_ok());
+
+        let messages = config.messages.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].name, "NMEA-GGA");
+    }
+}
+
+#[cfg(test)]
+mod ports {
+    use super::*;
+    use crate::ubxlib::ubx_cfg_prt::{PROTO_NMEA, PROTO_RTCM3};
+
+    #[test]
+    fn section_missing() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_mode_vehicle.conf");
+        assert!(res.is_ok());
+        assert!(config.ports.is_none());
+    }
+
+    #[test]
+    fn ok() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_ports_ok.conf");
+        assert!(res.is_ok());
+
+        let ports = config.ports.unwrap();
+        assert_eq!(ports.len(), 3);
+
+        assert_eq!(ports[0].port, PortId::Uart1);
+        assert_eq!(
+            ports[0].in_proto_mask,
+            Some(PROTO_UBX | PROTO_NMEA | PROTO_RTCM3)
+        );
+        assert_eq!(ports[0].out_proto_mask, Some(PROTO_UBX | PROTO_NMEA));
+        assert!(ports[0].baudrate.is_none());
+
+        assert_eq!(ports[1].port, PortId::Usb);
+        assert!(ports[1].in_proto_mask.is_none());
+        assert_eq!(ports[1].out_proto_mask, Some(PROTO_UBX));
+
+        assert_eq!(ports[2].port, PortId::Uart2);
+        assert_eq!(ports[2].baudrate, Some(38400));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut config: GnssMgrConfig = Default::default();
+        let res = config.parse_config("test_files/gnss0_ports_invalid.conf");
+        assert!(res.is_ok());
+
+        let ports = config.ports.unwrap();
+        assert_eq!(ports.len(), 1);
+        assert_eq!(ports[0].port, PortId::Spi);
+        assert_eq!(ports[0].in_proto_mask, Some(PROTO_UBX));
+    }
+
+    #[test]
+    fn from_args() {
+        let ports =
+            GnssMgrConfig::ports_from_args(&["usb-out=ubx", "uart2-baudrate=38400", "usb-in=nmea"])
+                .unwrap()
+                .unwrap();
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0].port, PortId::Usb);
+        assert_eq!(ports[0].in_proto_mask, Some(PROTO_NMEA));
+        assert_eq!(ports[0].out_proto_mask, Some(PROTO_UBX));
+        assert_eq!(ports[1].port, PortId::Uart2);
+        assert_eq!(ports[1].baudrate, Some(38400));
+
+        assert!(GnssMgrConfig::ports_from_args(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn from_args_invalid() {
+        assert!(GnssMgrConfig::ports_from_args(&["usb-out"]).is_err());
+        assert!(GnssMgrConfig::ports_from_args(&["uart1-out=nmea"]).is_err());
+        assert!(GnssMgrConfig::ports_from_args(&["uart1-baudrate=9600"]).is_err());
+        assert!(GnssMgrConfig::ports_from_args(&["usb-out=ubx", "can-out=ubx"]).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+/*
+Host supplied odometry input for sensor fusion
+
+Measurements are read from stdin (line based) or a SocketCAN interface and
+converted into UBX-ESF-MEAS measurements.
+
+Line format, whitespace separated key=value pairs, # starts a comment
+  t=<ms>                  optional time tag
+  speed=<m/s>             vehicle speed, negative when driving backward
+  tick=<n>                single tick counter
+  ticks=<fl>,<fr>,<rl>,<rr>  wheel tick counters
+  dir=fwd|rev             direction for tick values, default fwd
+
+Measurements without time tag get the time they are received, in ms since
+start of feed. Values are collected and sent at a fixed rate, the latest
+value of each measurement type with its own time tag.
+
+CAN frames, all values little endian
+  speed id:  bytes 0..3  speed in mm/s, i32
+  ticks id:  bytes 0..7  wheel tick counters fl, fr, rl, rr, u16 each
+             direction is taken from sign of last speed frame
+*/
+
+use std::ffi::CString;
+use std::io;
+use std::mem;
+
+use crate::ubxlib::ubx_esf_meas::{MeasType, Measurement};
+
+const WHEELS: [MeasType; 4] = [
+    MeasType::WheelTickFl,
+    MeasType::WheelTickFr,
+    MeasType::WheelTickRl,
+    MeasType::WheelTickRr,
+];
+
+// Accepts decimal or hex (0x prefix) ids, standard or extended
+pub fn parse_can_id(text: &str) -> Result<u32, String> {
+    let res = match text.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse::<u32>(),
+    };
+    match res {
+        Ok(id) if id <= 0x1FFF_FFFF => Ok(id),
+        _ => Err(format!("invalid CAN id {}", text)),
+    }
+}
+
+// Parses one input line, returns optional time tag and measurements
+pub fn parse_line(line: &str) -> Result<(Option<u32>, Vec<Measurement>), String> {
+    let line = match line.find('#') {
+        Some(pos) => &line[..pos],
+        None => line,
+    };
+
+    let mut time_tag = None;
+    let mut backward = false;
+    let mut speed = None;
+    let mut tick = None;
+    let mut ticks = None;
+
+    for item in line.split_whitespace() {
+        let mut kv = item.splitn(2, '=');
+        let key = kv.next().unwrap();
+        let value = kv.next().ok_or(format!("missing value for {}", key))?;
+        let invalid = || format!("invalid value {} for {}", value, key);
+
+        match key {
+            "t" => time_tag = Some(value.parse::<u32>().map_err(|_| invalid())?),
+            "speed" => speed = Some(value.parse::<f32>().map_err(|_| invalid())?),
+            "tick" => tick = Some(value.parse::<u32>().map_err(|_| invalid())?),
+            "ticks" => {
+                let values: Result<Vec<u32>, _> =
+                    value.split(',').map(|v| v.trim().parse::<u32>()).collect();
+                match values {
+                    Ok(values) if values.len() == 4 => ticks = Some(values),
+                    _ => return Err(invalid()),
+                }
+            }
+            "dir" => {
+                backward = match value {
+                    "fwd" => false,
+                    "rev" => true,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(format!("unknown key {}", key)),
+        }
+    }
+
+    let mut meas = Vec::new();
+    if let Some(ticks) = ticks {
+        for (wheel, value) in WHEELS.iter().zip(ticks) {
+            meas.push(Measurement::ticks(*wheel, value, backward));
+        }
+    }
+    if let Some(tick) = tick {
+        meas.push(Measurement::ticks(MeasType::SingleTick, tick, backward));
+    }
+    if let Some(speed) = speed {
+        meas.push(Measurement::speed((speed * 1000.0).round() as i32));
+    }
+
+    Ok((time_tag, meas))
+}
+
+/*
+Latest measurement of each type until next send slot
+
+A message has a single time tag, so measurements with different time tags
+are sent in separate messages, oldest first.
+*/
+#[derive(Default)]
+pub struct Pending {
+    meas: Vec<(u32, Measurement)>,
+}
+
+impl Pending {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, time_tag: u32, meas: Vec<Measurement>) {
+        for m in meas {
+            self.meas.retain(|(_, p)| p.data_type != m.data_type);
+            self.meas.push((time_tag, m));
+        }
+    }
+
+    // Returns measurements grouped by time tag and clears them
+    pub fn take(&mut self) -> Vec<(u32, Vec<Measurement>)> {
+        let mut tags: Vec<u32> = self.meas.iter().map(|(t, _)| *t).collect();
+        tags.sort_unstable();
+        tags.dedup();
+
+        let groups = tags
+            .iter()
+            .map(|tag| {
+                let meas = self
+                    .meas
+                    .iter()
+                    .filter(|(t, _)| t == tag)
+                    .map(|(_, m)| *m)
+                    .collect();
+                (*tag, meas)
+            })
+            .collect();
+        self.meas.clear();
+        groups
+    }
+}
+
+pub struct CanDecoder {
+    pub speed_id: u32,
+    pub ticks_id: u32,
+    backward: bool,
+}
+
+impl CanDecoder {
+    pub fn new(speed_id: u32, ticks_id: u32) -> Self {
+        Self {
+            speed_id,
+            ticks_id,
+            backward: false,
+        }
+    }
+
+    // Converts CAN frame to measurements, unrelated frames give an empty list
+    pub fn decode(&mut self, id: u32, data: &[u8]) -> Vec<Measurement> {
+        let mut meas = Vec::new();
+
+        if id == self.speed_id && data.len() >= 4 {
+            let speed = i32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            self.backward = speed < 0;
+            meas.push(Measurement::speed(speed));
+        } else if id == self.ticks_id && data.len() >= 8 {
+            for (i, wheel) in WHEELS.iter().enumerate() {
+                let ticks = u16::from_le_bytes([data[2 * i], data[2 * i + 1]]);
+                meas.push(Measurement::ticks(*wheel, ticks as u32, self.backward));
+            }
+        }
+
+        meas
+    }
+}
+
+// Kernel structures, see linux/can.h
+#[repr(C)]
+struct SockaddrCan {
+    can_family: libc::sa_family_t,
+    can_ifindex: libc::c_int,
+    rx_id: u32,
+    tx_id: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct CanFrame {
+    can_id: u32,
+    can_dlc: u8,
+    pad: u8,
+    res0: u8,
+    res1: u8,
+    data: [u8; 8],
+}
+
+const CAN_RAW: libc::c_int = 1;
+const CAN_EFF_FLAG: u32 = 0x8000_0000;
+const CAN_EFF_MASK: u32 = 0x1FFF_FFFF;
+const CAN_SFF_MASK: u32 = 0x0000_07FF;
+
+// Minimal SocketCAN raw socket, receive only
+pub struct CanSocket {
+    fd: libc::c_int,
+}
+
+impl CanSocket {
+    pub fn open(ifname: &str) -> Result<Self, String> {
+        let name = CString::new(ifname).map_err(|_| "invalid interface name")?;
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 {
+            return Err(format!("CAN interface {} not found", ifname));
+        }
+
+        let fd = unsafe { libc::socket(libc::PF_CAN, libc::SOCK_RAW, CAN_RAW) };
+        if fd < 0 {
+            return Err("can't create CAN socket".to_string());
+        }
+        let socket = Self { fd };
+
+        let addr = SockaddrCan {
+            can_family: libc::AF_CAN as libc::sa_family_t,
+            can_ifindex: ifindex as libc::c_int,
+            rx_id: 0,
+            tx_id: 0,
+        };
+        let res = unsafe {
+            libc::bind(
+                socket.fd,
+                &addr as *const SockaddrCan as *const libc::sockaddr,
+                mem::size_of::<SockaddrCan>() as libc::socklen_t,
+            )
+        };
+        if res < 0 {
+            return Err(format!("can't bind to CAN interface {}", ifname));
+        }
+
+        Ok(socket)
+    }
+
+    // Blocks until next frame is received, returns id and data
+    pub fn read(&self) -> Result<(u32, Vec<u8>), String> {
+        let mut frame = CanFrame::default();
+        let size = mem::size_of::<CanFrame>();
+        loop {
+            let res = unsafe {
+                libc::read(
+                    self.fd,
+                    &mut frame as *mut CanFrame as *mut libc::c_void,
+                    size,
+                )
+            };
+            if res == size as isize {
+                break;
+            }
+
+            // Interrupted by signal before anything was read, try again
+            let err = io::Error::last_os_error();
+            if res < 0 && err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(format!("CAN read failed ({})", err));
+        }
+
+        let id = if frame.can_id & CAN_EFF_FLAG != 0 {
+            frame.can_id & CAN_EFF_MASK
+        } else {
+            frame.can_id & CAN_SFF_MASK
+        };
+        let len = (frame.can_dlc as usize).min(8);
+        Ok((id, frame.data[..len].to_vec()))
+    }
+}
+
+impl Drop for CanSocket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_speed() {
+        let (time_tag, meas) = parse_line("t=1000 speed=13.89").unwrap();
+        assert_eq!(time_tag, Some(1000));
+        assert_eq!(meas, vec![Measurement::speed(13890)]);
+    }
+
+    #[test]
+    fn line_ticks() {
+        let (time_tag, meas) = parse_line("ticks=1,2,3,4 dir=rev  # comment").unwrap();
+        assert_eq!(time_tag, None);
+        assert_eq!(meas.len(), 4);
+        assert_eq!(meas[0], Measurement::ticks(MeasType::WheelTickFl, 1, true));
+        assert_eq!(meas[3], Measurement::ticks(MeasType::WheelTickRr, 4, true));
+    }
+
+    #[test]
+    fn line_empty() {
+        let (_, meas) = parse_line("# only a comment").unwrap();
+        assert!(meas.is_empty());
+        let (_, meas) = parse_line("").unwrap();
+        assert!(meas.is_empty());
+    }
+
+    #[test]
+    fn line_invalid() {
+        assert!(parse_line("speed=fast").is_err());
+        assert!(parse_line("ticks=1,2,3").is_err());
+        assert!(parse_line("dir=left").is_err());
+        assert!(parse_line("foo=1").is_err());
+        assert!(parse_line("speed").is_err());
+    }
+
+    #[test]
+    fn pending_latest_value() {
+        let mut dut = Pending::new();
+        assert!(dut.take().is_empty());
+
+        dut.add(100, vec![Measurement::speed(1000)]);
+        dut.add(120, vec![Measurement::speed(1100)]);
+        assert_eq!(dut.take(), vec![(120, vec![Measurement::speed(1100)])]);
+        assert!(dut.take().is_empty());
+    }
+
+    #[test]
+    fn pending_time_tags() {
+        let mut dut = Pending::new();
+        dut.add(
+            130,
+            vec![Measurement::ticks(MeasType::SingleTick, 5, false)],
+        );
+        dut.add(100, vec![Measurement::speed(1000)]);
+
+        // Each measurement keeps its own time tag, oldest first
+        let groups = dut.take();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0], (100, vec![Measurement::speed(1000)]));
+        assert_eq!(groups[1].0, 130);
+        assert!(dut.take().is_empty());
+    }
+
+    #[test]
+    fn can_ids() {
+        assert_eq!(parse_can_id("0x3e8"), Ok(0x3e8));
+        assert_eq!(parse_can_id("1001"), Ok(1001));
+        assert_eq!(parse_can_id("0x1fffffff"), Ok(0x1fff_ffff));
+        assert!(parse_can_id("0x20000000").is_err());
+        assert!(parse_can_id("abc").is_err());
+    }
+
+    #[test]
+    fn can_frames() {
+        let mut dut = CanDecoder::new(0x3e8, 0x3e9);
+
+        let meas = dut.decode(0x3e8, &(-1000i32).to_le_bytes());
+        assert_eq!(meas, vec![Measurement::speed(-1000)]);
+
+        let meas = dut.decode(0x3e9, &[1, 0, 2, 0, 3, 0, 0xff, 0xff]);
+        assert_eq!(meas.len(), 4);
+        assert_eq!(meas[1], Measurement::ticks(MeasType::WheelTickFr, 2, true));
+        assert_eq!(
+            meas[3],
+            Measurement::ticks(MeasType::WheelTickRr, 0xffff, true)
+        );
+
+        assert!(dut.decode(0x123, &[0; 8]).is_empty());
+        assert!(dut.decode(0x3e9, &[0; 4]).is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+/*
+Firmware compatibility table
+
+Known receiver firmwares, keyed by module model (as reported in MON-VER
+MOD=, e.g. NEO-M8L-0) and firmware version (FWVER=, e.g. ADR 4.31).
+Each entry records whether the version is recommended, deprecated or known
+to be bad and which optional features it supports.
+
+To support a new module or firmware, add an entry to FIRMWARES. Only mark a
+version deprecated or known bad with a reference to the u-blox release notes
+or errata stating why, init warns users about these versions.
+*/
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Feature {
+    Imu,    // Sensor fusion (ESF-xxx messages, IMU installation settings)
+    Hnr,    // High navigation rate output (HNR-xxx messages)
+    Timing, // Timing features (TIM-xxx messages, survey-in, fixed position mode)
+}
+
+impl Feature {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::Imu => "IMU",
+            Feature::Hnr => "HNR",
+            Feature::Timing => "TIMING",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FwStatus {
+    Recommended,
+    #[allow(dead_code)] // no documented entries yet
+    Deprecated,
+    #[allow(dead_code)] // no documented entries yet
+    KnownBad,
+    Unknown, // Not listed in table
+}
+
+impl fmt::Display for FwStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FwStatus::Recommended => write!(f, "recommended"),
+            FwStatus::Deprecated => write!(f, "deprecated"),
+            FwStatus::KnownBad => write!(f, "known bad"),
+            FwStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+struct FwEntry {
+    model: &'static str, // Model prefix, variant suffix (-0, -1, ..) is ignored
+    fw_ver: &'static str,
+    status: FwStatus,
+    features: &'static [Feature],
+}
+
+const ADR_FEATURES: &[Feature] = &[Feature::Imu, Feature::Hnr];
+const SPG_FEATURES: &[Feature] = &[];
+const TIM_FEATURES: &[Feature] = &[Feature::Timing];
+
+// Current u-blox release for each module
+#[rustfmt::skip]
+const FIRMWARES: [FwEntry; 3] = [
+    FwEntry { model: "NEO-M8L", fw_ver: "ADR 4.31", status: FwStatus::Recommended, features: ADR_FEATURES },
+    FwEntry { model: "NEO-M8N", fw_ver: "SPG 3.01", status: FwStatus::Recommended, features: SPG_FEATURES },
+    FwEntry { model: "NEO-M8T", fw_ver: "TIM 1.10", status: FwStatus::Recommended, features: TIM_FEATURES },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FwInfo {
+    pub model: String,
+    pub fw_ver: String,
+    pub status: FwStatus,
+    pub features: Vec<Feature>,
+    pub recommended: Option<String>, // Recommended version for this model
+}
+
+impl FwInfo {
+    // Looks up model and firmware version in firmware table
+    pub fn lookup(model: &str, fw_ver: &str) -> Self {
+        Self::lookup_in(&FIRMWARES, model, fw_ver)
+    }
+
+    fn lookup_in(table: &[FwEntry], model: &str, fw_ver: &str) -> Self {
+        let for_model = |e: &&FwEntry| model.starts_with(e.model);
+        let recommended = table
+            .iter()
+            .filter(for_model)
+            .find(|e| e.status == FwStatus::Recommended)
+            .map(|e| String::from(e.fw_ver));
+
+        let (status, features) = match table.iter().filter(for_model).find(|e| e.fw_ver == fw_ver) {
+            Some(e) => (e.status, e.features.to_vec()),
+            None => (FwStatus::Unknown, Self::family_features(table, fw_ver)),
+        };
+
+        Self {
+            model: String::from(model),
+            fw_ver: String::from(fw_ver),
+            status,
+            features,
+            recommended,
+        }
+    }
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    pub fn features_to_string(&self) -> String {
+        let names: Vec<&str> = self.features.iter().map(|f| f.name()).collect();
+        names.join(";")
+    }
+
+    // Unlisted firmwares get the features of their firmware family (e.g. ADR)
+    fn family_features(table: &[FwEntry], fw_ver: &str) -> Vec<Feature> {
+        let family = fw_ver.split(' ').next().unwrap_or("");
+        match table
+            .iter()
+            .find(|e| e.fw_ver.split(' ').next() == Some(family))
+        {
+            Some(e) => e.features.to_vec(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommended() {
+        let dut = FwInfo::lookup("NEO-M8L-0", "ADR 4.31");
+        assert_eq!(dut.status, FwStatus::Recommended);
+        assert_eq!(dut.recommended, Some(String::from("ADR 4.31")));
+        assert!(dut.supports(Feature::Imu));
+        assert!(dut.supports(Feature::Hnr));
+        assert!(!dut.supports(Feature::Timing));
+        assert_eq!(dut.features_to_string(), "IMU;HNR");
+    }
+
+    // Made up versions, the real table has no such entries yet
+    #[rustfmt::skip]
+    const TEST_FIRMWARES: [FwEntry; 3] = [
+        FwEntry { model: "NEO-M8L", fw_ver: "ADR 9.20", status: FwStatus::Recommended, features: ADR_FEATURES },
+        FwEntry { model: "NEO-M8L", fw_ver: "ADR 9.10", status: FwStatus::Deprecated, features: ADR_FEATURES },
+        FwEntry { model: "NEO-M8L", fw_ver: "ADR 9.00", status: FwStatus::KnownBad, features: ADR_FEATURES },
+    ];
+
+    #[test]
+    fn deprecated_and_bad() {
+        let dut = FwInfo::lookup_in(&TEST_FIRMWARES, "NEO-M8L-0", "ADR 9.10");
+        assert_eq!(dut.status, FwStatus::Deprecated);
+        assert_eq!(dut.recommended, Some(String::from("ADR 9.20")));
+        assert!(dut.supports(Feature::Imu));
+
+        let dut = FwInfo::lookup_in(&TEST_FIRMWARES, "NEO-M8L-0", "ADR 9.00");
+        assert_eq!(dut.status, FwStatus::KnownBad);
+    }
+
+    #[test]
+    fn older_versions_untested() {
+        let dut = FwInfo::lookup("NEO-M8L-0", "ADR 4.21");
+        assert_eq!(dut.status, FwStatus::Unknown);
+        assert!(dut.supports(Feature::Imu));
+    }
+
+    #[test]
+    fn other_models() {
+        let dut = FwInfo::lookup("NEO-M8N-0", "SPG 3.01");
+        assert_eq!(dut.status, FwStatus::Recommended);
+        assert!(!dut.supports(Feature::Imu));
+        assert_eq!(dut.features_to_string(), "");
+
+        let dut = FwInfo::lookup("NEO-M8T-0", "TIM 1.10");
+        assert_eq!(dut.status, FwStatus::Recommended);
+        assert!(dut.supports(Feature::Timing));
+        assert!(!dut.supports(Feature::Imu));
+    }
+
+    #[test]
+    fn unknown() {
+        // Unlisted version of known family
+        let dut = FwInfo::lookup("NEO-M8L-0", "ADR 5.00");
+        assert_eq!(dut.status, FwStatus::Unknown);
+        assert_eq!(dut.recommended, Some(String::from("ADR 4.31")));
+        assert!(dut.supports(Feature::Imu));
+
+        // Firmware not matching model
+        let dut = FwInfo::lookup("NEO-M8N-0", "ADR 4.31");
+        assert_eq!(dut.status, FwStatus::Unknown);
+        assert_eq!(dut.recommended, Some(String::from("SPG 3.01")));
+
+        // Completely unknown module
+        let dut = FwInfo::lookup("ZED-F9P", "HPG 1.13");
+        assert_eq!(dut.status, FwStatus::Unknown);
+        assert_eq!(dut.recommended, None);
+        assert!(dut.features.is_empty());
+    }
+}
@@ -3,60 +3,132 @@ use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use chrono::{SecondsFormat, Utc};
 use clap::ArgMatches;
-use log::{debug, info};
+use log::{debug, info, warn};
 
-use crate::config_file::GnssMgrConfig;
+use crate::atomic_file;
+use crate::clock::{self, RealtimeClock, SystemClock, TimeSample};
+use crate::config_file::{GnssMgrConfig, PortConfig};
+use crate::feed::{parse_can_id, parse_line, CanDecoder, CanSocket, Pending};
+use crate::firmware::{Feature, FwInfo, FwStatus};
 use crate::neo_m8::NeoM8;
+use crate::ntp_shm::{NtpShm, Refclock};
+use crate::state::LastPosition;
+use crate::stop_signal;
+use crate::timemark::MarkEvent;
+use crate::ubxlib::cid::UbxCID;
 use crate::ubxlib::error::Error;
+use crate::ubxlib::frame::{UbxFrame, UbxFrameDeSerialize};
+use crate::ubxlib::gps_time;
 use crate::ubxlib::ubx_cfg_esfla::LeverArmType;
-
-static CURRENT_FW_VER: &str = "ADR 4.31";
+use crate::ubxlib::ubx_cfg_esfwt::{FLAG_DIR_PIN_POL, FLAG_USE_WT_SPEED};
+use crate::ubxlib::ubx_cfg_prt::{protocols_to_string, PortId};
+use crate::ubxlib::ubx_cfg_tmode2::DataCfgTmode2;
+use crate::ubxlib::ubx_cfg_tp5::{
+    DataCfgTp5, TimePulse, FLAG_ACTIVE, FLAG_ALIGN_TO_TOW, FLAG_IS_FREQ, FLAG_IS_LENGTH,
+    FLAG_LOCKED_OTHER_SET, FLAG_LOCK_GNSS_FREQ, FLAG_POLARITY,
+};
+use crate::ubxlib::ubx_esf_alg::DataEsfAlg;
+use crate::ubxlib::ubx_esf_ins::UbxEsfIns;
+use crate::ubxlib::ubx_esf_meas::{Measurement, UbxEsfMeas};
+use crate::ubxlib::ubx_esf_raw::{SensorSample, UbxEsfRaw};
+use crate::ubxlib::ubx_esf_status::UbxEsfStatus;
+use crate::ubxlib::ubx_hnr_att::UbxHnrAtt;
+use crate::ubxlib::ubx_hnr_pvt::UbxHnrPvt;
+use crate::ubxlib::ubx_mga_data::UbxMgaData;
+use crate::ubxlib::ubx_mga_init_time_utc::TimeRef;
+use crate::ubxlib::ubx_nav_att::{DataNavAtt, UbxNavAtt};
+use crate::ubxlib::ubx_nav_pvt::UbxNavPvt;
+use crate::ubxlib::ubx_nav_timeutc::UbxNavTimeUtc;
+use crate::ubxlib::ubx_tim_tm2::UbxTimTm2;
+use crate::ubxlib::ubx_tim_tp::UbxTimTp;
+use crate::ubxlib::ubx_upd_sos::Response;
 
 pub struct GnssMgr {
     device_name: String,
+    run_dir: PathBuf,
+    config_dir: PathBuf,
+    state_dir: PathBuf,
+    firmware: Option<FwInfo>,
     modem: NeoM8,
 }
 
 impl GnssMgr {
+    // IERS list of leap seconds as provided by tzdata
+    const LEAP_SECONDS_LIST: &'static str = "/usr/share/zoneinfo/leap-seconds.list";
+
     pub fn new(device: &str) -> Self {
         Self {
             device_name: String::from(device),
+            run_dir: PathBuf::from("/run/gnss"),
+            config_dir: PathBuf::from("/etc/gnss"),
+            state_dir: PathBuf::from("/var/lib/gnss"),
+            firmware: None,
             modem: NeoM8::new(device),
         }
     }
 
-    pub fn prepare_port(&mut self, _bitrate: Option<u32>) -> Result<(), String> {
-        if _bitrate.is_none() {
-            // Check bitrate and change to 115'200 if different
-            info!("detecting current bitrate");
+    pub fn set_run_dir(&mut self, dir: &str) {
+        self.run_dir = PathBuf::from(dir);
+    }
+
+    pub fn set_config_dir(&mut self, dir: &str) {
+        self.config_dir = PathBuf::from(dir);
+    }
+
+    pub fn set_state_dir(&mut self, dir: &str) {
+        self.state_dir = PathBuf::from(dir);
+    }
 
-            let bit_rate_current = match self.modem.detect_baudrate_active() {
-                Ok(bitrate) => bitrate,
-                Err(e) => return Err(format!("bitrate detection failed ({})", e)),
+    pub fn prepare_port(&mut self, bitrate: u32, detection: Option<&str>) -> Result<(), String> {
+        if let Some(mode) = detection {
+            // Check bitrate and change to desired bitrate if different
+            info!("detecting current bitrate ({})", mode);
+
+            let res = match mode {
+                "passive" => self.modem.detect_baudrate_passive(),
+                "active" => self.modem.detect_baudrate_active(),
+                _ => self.modem.detect_baudrate(),
             };
+            let port_info = res.map_err(|e| format!("bitrate detection failed ({})", e))?;
 
-            info!("detected bitrate {:?} bps", bit_rate_current);
-            if bit_rate_current != 115200 {
-                info!("changing bitrate from {} to 115200 bps", bit_rate_current);
+            let bit_rate_current = port_info.baudrate;
+            info!(
+                "detected bitrate {:?} bps, output protocols {}",
+                bit_rate_current,
+                protocols_to_string(port_info.out_proto_mask)
+            );
+            if bit_rate_current != bitrate as usize {
+                info!(
+                    "changing bitrate from {} to {} bps",
+                    bit_rate_current, bitrate
+                );
                 self.modem
                     .configure(bit_rate_current)
                     .map_err(|err| err.to_string())?;
                 self.modem
-                    .set_modem_baudrate(115200)
+                    .set_modem_baudrate(bitrate)
                     .map_err(|err| err.to_string())?;
             }
         }
 
         self.modem
-            .configure(115200)
+            .configure(bitrate as usize)
             .map_err(|err| err.to_string())?;
 
         Ok(())
     }
 
-    pub fn run_init(&mut self, _matches: &ArgMatches) -> Result<(), String> {
+    pub fn run_init(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        // Check port settings before touching the modem
+        let args: Vec<&str> = matches.values_of("port").unwrap_or_default().collect();
+        let ports = GnssMgrConfig::ports_from_args(&args)?;
+
         // vendor is always "ublox" when using this library
         let mut info: HashMap<&str, String> = HashMap::new();
         info.insert("vendor", String::from("ublox"));
@@ -67,34 +139,54 @@ impl GnssMgr {
             .version(&mut info)
             .map_err(|e| format!("can't get modem information ({})", e))?;
 
-        // .. create run file
-        let runfile_path = Self::build_runfile_path(&self.device_name);
-        Self::write_runfile(&runfile_path, &info)
+        // .. check firmware against compatibility table ..
+        let fw = FwInfo::lookup(&info["model"], &info["fw_ver"]);
+        Self::check_firmware(&fw);
+
+        // .. create run files, human readable and key=value format
+        let name = Self::stable_name(Path::new(&self.device_name), Path::new("/dev"));
+        let runfile_path = self.run_dir.join(format!("{}.config", name));
+        Self::write_runfile(&runfile_path, &info, &fw)
             .map_err(|e| format!("can't create run file ({})", e))?;
 
         info!("GNSS run file {} created", runfile_path.display());
 
+        let varsfile_path = self.run_dir.join(format!("{}.env", name));
+        Self::write_runfile_vars(&varsfile_path, &info, &fw)
+            .map_err(|e| format!("can't create run file ({})", e))?;
+
+        self.firmware = Some(fw);
+
         // Change protocol to NMEA 4.1
         info!("setting nmea version");
         self.modem
             .set_nmea_protocol_version("4.1")
             .map_err(|e| format!("can't set NMEA protocol version ({})", e))?;
 
+        if let Some(ports) = &ports {
+            info!("setting port protocols and baudrates");
+            self.configure_ports(ports)?;
+        }
+
         Ok(())
     }
 
     pub fn run_config(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        if matches.is_present("show") {
+            return self.show_config();
+        }
+
         // Check for optional config file name
         let configfile_path = matches.value_of("configfile");
         let configfile_path = match configfile_path {
             Some(path) => PathBuf::from(path), // path to file specified
-            _ => Self::build_configfile_path(&self.device_name), // left away, compute from device name
+            _ => self.build_configfile_path(), // left away, compute from device name
         };
 
         // Get configuration from config file
         info!("using configfile {}", configfile_path.display());
         let mut config: GnssMgrConfig = Default::default();
-        let _res = config.parse_config(&configfile_path)?;
+        config.parse_config(&configfile_path)?;
 
         // Apply configuration to modem
         info!("configuring modem");
@@ -108,6 +200,10 @@ impl GnssMgr {
         let action = matches.value_of("action").unwrap();
         debug!("control action {:?}", action);
 
+        if matches.is_present("clear-calibration") && action != "reset-fusion" {
+            return Err("--clear-calibration requires action reset-fusion".to_string());
+        }
+
         match action {
             "cold-start" => {
                 info!("Cold boot of GNSS receiver triggered, let receiver start");
@@ -123,26 +219,186 @@ impl GnssMgr {
                 info!("Persisting receiver configuration");
                 self.modem.persist().map_err(|err| err.to_string())?
             }
+            "reset-fusion" => self.reset_fusion(matches.is_present("clear-calibration"))?,
+            _ => return Err("Unknown command".to_string()),
+        };
+
+        Ok(())
+    }
+
+    fn reset_fusion(&mut self, clear_calibration: bool) -> Result<(), String> {
+        self.require(Feature::Imu)?;
+
+        if clear_calibration {
+            info!("Clearing sensor calibration, receiver restarts");
+            self.modem
+                .clear_sensor_calibration()
+                .map_err(|err| err.to_string())?;
+        }
+
+        info!("Resetting IMU alignment");
+        self.modem
+            .reset_alignment()
+            .map_err(|err| err.to_string())?;
+
+        // Fusion filter must restart with initialization phase
+        let start = Instant::now();
+        loop {
+            let status = self.modem.esf_status().map_err(|err| err.to_string())?;
+            if status.initializing() {
+                info!("sensor fusion is initializing");
+                return Ok(());
+            }
+            if start.elapsed() >= Duration::from_secs(5) {
+                return Err(format!(
+                    "sensor fusion did not restart, mode is {}",
+                    status.fusion_mode_name()
+                ));
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    // GPS to UTC leap seconds known by host, None if list is missing or expired
+    fn host_leap_seconds() -> Option<i8> {
+        let list = fs::read_to_string(Self::LEAP_SECONDS_LIST)
+            .map_err(|err| debug!("{}: {}", Self::LEAP_SECONDS_LIST, err))
+            .ok()?;
+        let leap_secs = gps_time::leap_seconds_from_list(&list, Utc::now());
+        if leap_secs.is_none() {
+            info!(
+                "leap seconds unknown, {} expired or invalid",
+                Self::LEAP_SECONDS_LIST
+            );
+        }
+        leap_secs.map(|leap_secs| leap_secs as i8)
+    }
+
+    pub fn run_assist(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        let action = matches.value_of("action").unwrap();
+        debug!("assist action {:?}", action);
+
+        match action {
+            "time" => {
+                // unwrap must never fail here, as arguments have default values and are checked by parser
+                let accuracy = matches
+                    .value_of("accuracy")
+                    .unwrap()
+                    .parse::<f64>()
+                    .unwrap();
+                let leap_secs = match matches.value_of("leap-seconds") {
+                    Some(leap_secs) => Some(leap_secs.parse::<i8>().unwrap()),
+                    None => Self::host_leap_seconds(),
+                };
+                let time_ref = match matches.value_of("extint") {
+                    Some("0") => TimeRef::ExtInt0,
+                    Some("1") => TimeRef::ExtInt1,
+                    _ => TimeRef::Receipt,
+                };
+
+                self.modem
+                    .set_assistance_time(
+                        Utc::now,
+                        Duration::from_secs_f64(accuracy),
+                        leap_secs,
+                        time_ref,
+                    )
+                    .map_err(|err| err.to_string())?;
+                info!("Assistance time accepted by receiver");
+            }
+            "load" => {
+                // unwrap must never fail here, parser checks file argument for load action
+                let path = matches.value_of("file").unwrap();
+                self.load_assistance(path, !matches.is_present("no-ack"))?;
+            }
             _ => return Err("Unknown command".to_string()),
         };
 
         Ok(())
     }
 
-    pub fn run_sos(&mut self, matches: &ArgMatches) -> Result<(), String> {
+    fn load_assistance(&mut self, path: &str, ack: bool) -> Result<(), String> {
+        let data = fs::read(path).map_err(|e| format!("can't read {} ({})", path, e))?;
+        let messages =
+            UbxMgaData::split(&data).map_err(|err| format!("can't parse {} ({})", path, err))?;
+        info!(
+            "sending {} assistance messages from {}",
+            messages.len(),
+            path
+        );
+
+        if ack {
+            self.modem.enable_mga_ack().map_err(|err| err.to_string())?;
+        }
+
+        let mut accepted = 0;
+        let mut rejected = 0;
+        for msg in &messages {
+            match self.modem.send_mga(msg, ack) {
+                Ok(_) => accepted += 1,
+                Err(Error::ModemNAK) => rejected += 1,
+                Err(err) => return Err(err.to_string()),
+            }
+        }
+
+        if ack {
+            println!("Messages:           {}", messages.len());
+            println!("Accepted:           {}", accepted);
+            println!("Rejected:           {}", rejected);
+        } else {
+            println!("Messages sent:      {}", accepted);
+        }
+        Ok(())
+    }
+
+    // Returns exit code, see sos_status
+    pub fn run_sos(&mut self, matches: &ArgMatches) -> Result<i32, String> {
         let action = matches.value_of("action").unwrap();
         debug!("sos action {:?}", action);
 
+        let host_file = matches.value_of("host");
+        if host_file.is_some() && !matches!(action, "save" | "restore") {
+            return Err("--host requires action save or restore".to_string());
+        }
+
         match action {
             "save" => {
-                self.modem.sos_save().map_err(|err| err.to_string())?;
-                info!("Saving receiver state successfully performed");
+                // Position must be read before receiver is stopped
+                self.save_position();
+                match host_file {
+                    Some(path) => {
+                        self.save_database(path)?;
+                        info!("Navigation database saved to {}", path);
+                    }
+                    None => {
+                        self.modem.sos_save().map_err(|err| err.to_string())?;
+                        info!("Saving receiver state successfully performed");
+                    }
+                }
+                Some(())
+            }
+            "restore" => {
+                // unwrap must never fail here, parser requires host file for restore action
+                let path = host_file.unwrap();
+                self.load_assistance(path, true)?;
+                info!("Navigation database restored from {}", path);
                 Some(())
             }
             "clear" => {
-                self.modem
-                    .set_assistance_time()
-                    .map_err(|err| err.to_string())?;
+                // Time is only a hint for the restart, clearing proceeds without confirmation
+                match self.modem.set_assistance_time(
+                    Utc::now,
+                    Duration::from_secs(10),
+                    Self::host_leap_seconds(),
+                    TimeRef::Receipt,
+                ) {
+                    Ok(()) => (),
+                    Err(err @ Error::ModemNotResponding) | Err(err @ Error::ModemNAK) => {
+                        warn!("assistance time not confirmed ({})", err)
+                    }
+                    Err(err) => return Err(err.to_string()),
+                }
+                self.restore_position();
 
                 match self.modem.sos_check() {
                     Ok(_) => {
@@ -159,9 +415,911 @@ impl GnssMgr {
                 }
                 Some(())
             }
+            "status" => return self.sos_status(),
             _ => return Err("Unknown command".to_string()),
         };
 
+        Ok(0)
+    }
+
+    /*
+    Reports result of restoring the save on shutdown backup at receiver startup,
+    result is also given as exit code
+      0: backup restored at startup
+      2: no backup present at startup
+      3: restoring backup failed
+      4: state unknown
+
+    The receiver only reports the startup result, a backup created by a later
+    sos save is not reflected.
+    */
+    fn sos_status(&mut self) -> Result<i32, String> {
+        let state = self.modem.sos_state().map_err(|err| err.to_string())?;
+
+        let (backup, restore, ec) = match state {
+            Response::Restored => ("present", "successful", 0),
+            Response::NotRestoredNoBackup => ("none", "not performed", 2),
+            Response::RestoreFailed => ("present", "failed", 3),
+            Response::Unknown => ("unknown", "unknown", 4),
+        };
+        println!("Backup at startup:  {}", backup);
+        println!("Startup restore:    {}", restore);
+
+        Ok(ec)
+    }
+
+    pub fn run_imu_status(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        self.require(Feature::Imu)?;
+
+        // unwrap must never fail here, as argument has a default value and is checked by parser
+        let wait = matches.value_of("wait");
+        let timeout = matches.value_of("timeout").unwrap().parse::<u64>().unwrap();
+        let start = Instant::now();
+
+        loop {
+            let status = self.modem.esf_status().map_err(|err| err.to_string())?;
+            let alg = self.modem.esf_alg().map_err(|err| err.to_string())?;
+
+            let reached = match wait {
+                Some("fusion") => status.fusion(),
+                Some("calibrated") => status.calibrated(),
+                Some("aligned") => alg.aligned(),
+                _ => true,
+            };
+
+            if reached || start.elapsed() >= Duration::from_secs(timeout) {
+                // Attitude is only output once fusion has a solution
+                let att = match self.modem.nav_att() {
+                    Ok(att) => Some(att),
+                    Err(err) => {
+                        info!("no attitude available: {}", err);
+                        None
+                    }
+                };
+                Self::print_imu_status(&status, &alg, att.as_ref());
+                if !reached {
+                    return Err(format!("timeout waiting for state {}", wait.unwrap()));
+                }
+                return Ok(());
+            }
+
+            info!(
+                "waiting for {}, fusion: {}, alignment: {}",
+                wait.unwrap(),
+                status.fusion_mode_name(),
+                alg.status_name()
+            );
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    pub fn run_imu_align(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        self.require(Feature::Imu)?;
+
+        let configfile_path = match matches.value_of("configfile") {
+            Some(path) => PathBuf::from(path),
+            _ => self.build_configfile_path(),
+        };
+
+        // Alignment must have converged, otherwise angles are meaningless
+        let alg = self.modem.esf_alg().map_err(|err| err.to_string())?;
+        if !alg.aligned() && !matches.is_present("force") {
+            return Err(format!(
+                "IMU alignment not completed ({}), drive vehicle and retry",
+                alg.status_name()
+            ));
+        }
+
+        let yaw = alg.yaw as f32 / 100.0;
+        let pitch = alg.pitch as f32 / 100.0;
+        let roll = alg.roll as f32 / 100.0;
+        info!(
+            "estimated IMU angles yaw {:.2}, pitch {:.2}, roll {:.2} ({} alignment)",
+            yaw,
+            pitch,
+            roll,
+            alg.status_name()
+        );
+
+        // Store as fixed angles, automatic alignment no longer required
+        let values = [
+            ("yaw", format!("{:.2}", yaw)),
+            ("pitch", format!("{:.2}", pitch)),
+            ("roll", format!("{:.2}", roll)),
+            ("auto-align", String::from("false")),
+        ];
+        GnssMgrConfig::update_keys(&configfile_path, "installation", &values)?;
+        info!(
+            "angles written to {}, run config command to apply",
+            configfile_path.display()
+        );
+
+        Ok(())
+    }
+
+    pub fn run_survey(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        self.require(Feature::Timing)?;
+
+        // unwrap must never fail here, as arguments have default values and are checked by parser
+        let min_dur = matches
+            .value_of("min-duration")
+            .unwrap()
+            .parse::<u32>()
+            .unwrap();
+        let accuracy = matches
+            .value_of("accuracy")
+            .unwrap()
+            .parse::<f64>()
+            .unwrap();
+        let timeout = matches.value_of("timeout").unwrap().parse::<u64>().unwrap();
+
+        self.modem
+            .start_survey_in(min_dur, (accuracy * 1000.0).round() as u32)
+            .map_err(|err| format!("can't start survey-in ({})", err))?;
+        info!(
+            "survey-in started, minimum {} s, accuracy {:.3} m",
+            min_dur, accuracy
+        );
+
+        let start = Instant::now();
+        let svin = loop {
+            thread::sleep(Duration::from_secs(1));
+            let svin = self
+                .modem
+                .survey_in_status()
+                .map_err(|err| err.to_string())?;
+            println!(
+                "Survey-in:          {} s, {} observations, accuracy {:.3} m",
+                svin.dur,
+                svin.obs,
+                svin.accuracy()
+            );
+            if svin.completed() {
+                break svin;
+            }
+            if start.elapsed() >= Duration::from_secs(timeout) {
+                return Err(format!("survey-in not completed within {} s", timeout));
+            }
+        };
+
+        let ecef = format!(
+            "{:.2};{:.2};{:.2}",
+            svin.mean_x as f64 / 100.0,
+            svin.mean_y as f64 / 100.0,
+            svin.mean_z as f64 / 100.0
+        );
+        println!("Position:           ECEF {} m", ecef);
+        println!("Accuracy:           {:.3} m", svin.accuracy());
+
+        // Store as fixed position, survey-in no longer required on startup
+        if matches.is_present("store") {
+            let configfile_path = match matches.value_of("configfile") {
+                Some(path) => PathBuf::from(path),
+                _ => self.build_configfile_path(),
+            };
+            let values = [
+                ("mode", String::from("fixed")),
+                ("ecef", ecef),
+                ("llh", String::new()),
+                ("fixed-pos-acc", format!("{:.3}", svin.accuracy())),
+            ];
+            GnssMgrConfig::update_keys(&configfile_path, "timemode", &values)?;
+            info!(
+                "position written to {}, run config command to apply",
+                configfile_path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn run_feed(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        self.require(Feature::Imu)?;
+
+        // unwrap must never fail here, as arguments have default values and are checked by parser
+        let rate = matches.value_of("rate").unwrap().parse::<u64>().unwrap();
+        let period = Duration::from_millis(1000 / rate);
+
+        // Input source, returns None at end of input
+        type Input = Option<(Option<u32>, Vec<Measurement>)>;
+        let mut source: Box<dyn FnMut() -> Result<Input, String> + Send> =
+            match matches.value_of("interface") {
+                Some(ifname) => {
+                    let socket = CanSocket::open(ifname)?;
+                    let mut decoder = CanDecoder::new(
+                        parse_can_id(matches.value_of("speed-id").unwrap())?,
+                        parse_can_id(matches.value_of("ticks-id").unwrap())?,
+                    );
+                    info!("reading measurements from {}", ifname);
+                    Box::new(move || {
+                        let (id, data) = socket.read()?;
+                        Ok(Some((None, decoder.decode(id, &data))))
+                    })
+                }
+                None => {
+                    let stdin = std::io::stdin();
+                    info!("reading measurements from stdin");
+                    Box::new(move || {
+                        let mut line = String::new();
+                        match stdin.read_line(&mut line) {
+                            Ok(0) => Ok(None),
+                            Ok(_) => parse_line(&line).map(Some),
+                            Err(e) => Err(e.to_string()),
+                        }
+                    })
+                }
+            };
+
+        // Input is read in its own thread, so measurements are time tagged when
+        // they arrive and sending follows the rate timer, not the input
+        let start = Instant::now();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || loop {
+            let res = match source() {
+                Ok(Some((time_tag, meas))) => {
+                    // Without explicit time tag use ms since start of feed
+                    let time_tag = time_tag.unwrap_or(start.elapsed().as_millis() as u32);
+                    Ok((time_tag, meas))
+                }
+                Ok(None) => return,
+                Err(err) => Err(err),
+            };
+            let failed = res.is_err();
+            if tx.send(res).is_err() || failed {
+                return;
+            }
+        });
+
+        let mut pending = Pending::new();
+        let mut next_slot = Instant::now() + period;
+        let mut sent = 0;
+        let mut end_of_input = false;
+
+        while !end_of_input {
+            let timeout = next_slot.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(timeout) {
+                Ok(res) => {
+                    let (time_tag, meas) = res?;
+                    pending.add(time_tag, meas);
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => end_of_input = true,
+            }
+
+            let now = Instant::now();
+            if now < next_slot && !end_of_input {
+                continue;
+            }
+            next_slot += period;
+            if next_slot < now {
+                // Sending took longer than a period, don't try to catch up
+                next_slot = now + period;
+            }
+
+            for (time_tag, meas) in pending.take() {
+                self.modem
+                    .send_measurements(time_tag, &meas)
+                    .map_err(|err| err.to_string())?;
+                sent += 1;
+            }
+        }
+
+        info!("end of input, {} measurement messages sent", sent);
+        Ok(())
+    }
+
+    pub fn run_monitor(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        self.require(Feature::Hnr)?;
+
+        // unwrap must never fail here, as arguments have default values and are checked by parser
+        let count = matches.value_of("count").unwrap().parse::<usize>().unwrap();
+        let timeout = matches
+            .value_of("timeout")
+            .unwrap()
+            .parse::<u128>()
+            .unwrap();
+
+        let mut pvt = UbxHnrPvt::create();
+        let mut att = UbxHnrAtt::create();
+        let mut ins = UbxEsfIns::create();
+        let mut nav_att = UbxNavAtt::create();
+        let cids = [pvt.cid, att.cid, ins.cid, nav_att.cid];
+
+        // Messages must be enabled, e.g. UBX-HNR-PVT=1 in [messages] section
+        let mut printed = 0;
+        let frames = self
+            .modem
+            .listen(&cids, Some(timeout * 1000), |frame| {
+                if frame.cid == pvt.cid {
+                    pvt.from_bin(&frame.data);
+                    let d = &pvt.data;
+                    println!(
+                        "HNR-PVT {:>10} fix {} lat {:.7} lon {:.7} speed {:.2} m/s heading {:.2}",
+                        d.itow,
+                        if d.fix_ok() { d.gps_fix } else { 0 },
+                        d.lat as f64 * 1e-7,
+                        d.lon as f64 * 1e-7,
+                        d.g_speed as f64 / 1000.0,
+                        d.head_veh as f64 * 1e-5
+                    );
+                } else if frame.cid == att.cid {
+                    att.from_bin(&frame.data);
+                    let d = &att.data;
+                    println!(
+                        "HNR-ATT {:>10} roll {:.2} pitch {:.2} heading {:.2}",
+                        d.itow,
+                        d.roll as f64 * 1e-5,
+                        d.pitch as f64 * 1e-5,
+                        d.heading as f64 * 1e-5
+                    );
+                } else if frame.cid == ins.cid {
+                    ins.from_bin(&frame.data);
+                    let d = &ins.data;
+                    println!(
+                        "ESF-INS {:>10} rate {:.3};{:.3};{:.3} °/s accel {:.2};{:.2};{:.2} m/s²{}",
+                        d.itow,
+                        d.x_ang_rate as f64 / 1000.0,
+                        d.y_ang_rate as f64 / 1000.0,
+                        d.z_ang_rate as f64 / 1000.0,
+                        d.x_accel as f64 / 100.0,
+                        d.y_accel as f64 / 100.0,
+                        d.z_accel as f64 / 100.0,
+                        if d.valid() { "" } else { " (invalid)" }
+                    );
+                } else if frame.cid == nav_att.cid {
+                    nav_att.from_bin(&frame.data);
+                    let d = &nav_att.data;
+                    println!(
+                        "NAV-ATT {:>10} roll {:.2} pitch {:.2} heading {:.2} acc {:.2};{:.2};{:.2}",
+                        d.itow,
+                        d.roll as f64 * 1e-5,
+                        d.pitch as f64 * 1e-5,
+                        d.heading as f64 * 1e-5,
+                        d.acc_roll as f64 * 1e-5,
+                        d.acc_pitch as f64 * 1e-5,
+                        d.acc_heading as f64 * 1e-5
+                    );
+                }
+                printed += 1;
+                count == 0 || printed < count
+            })
+            .map_err(|err| err.to_string())?;
+
+        if frames == 0 {
+            return Err("no high rate messages received, check [messages] settings".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn run_time(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        // unwrap must never fail here, as arguments have default values and are checked by parser
+        let accuracy = matches
+            .value_of("accuracy")
+            .unwrap()
+            .parse::<u32>()
+            .unwrap();
+        let timeout = matches
+            .value_of("timeout")
+            .unwrap()
+            .parse::<u128>()
+            .unwrap();
+
+        let mut clock = RealtimeClock {};
+        let sample = self.receiver_time(&clock, accuracy, timeout)?;
+        let offset = sample.offset();
+
+        println!(
+            "Receiver time:      {}",
+            sample.utc.to_rfc3339_opts(SecondsFormat::Millis, true)
+        );
+        println!("Accuracy:           {} ns", sample.acc_ns);
+        println!(
+            "Offset:             {:+.3} s",
+            offset.num_milliseconds() as f64 / 1000.0
+        );
+
+        if matches.is_present("check") {
+            return Ok(());
+        }
+
+        if offset.num_milliseconds().abs() <= accuracy as i64 {
+            info!("system clock within {} ms, not changed", accuracy);
+        } else {
+            clock::adjust(&mut clock, &sample)?;
+            info!("system clock set");
+        }
+        Ok(())
+    }
+
+    pub fn run_ntp_shm(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        // unwrap must never fail here, as argument has a default value and is checked by parser
+        let unit = matches.value_of("unit").unwrap().parse::<u8>().unwrap();
+        let mut shm = NtpShm::open(unit)?;
+
+        let mut timeutc = UbxNavTimeUtc::create();
+        let mut tp = UbxTimTp::create();
+        let mut rates = self
+            .enable_messages(&[timeutc.cid])
+            .map_err(|err| format!("can't enable time messages ({})", err))?;
+        match self.enable_messages(&[tp.cid]) {
+            Ok(tp_rates) => rates.extend(tp_rates),
+            Err(err) => warn!("time pulse messages not available ({})", err),
+        }
+
+        // Runs until stopped, signal ends listening so message rates are restored
+        stop_signal::install()?;
+        info!("writing receiver time to NTP SHM unit {}", unit);
+        let mut refclock = Refclock::new();
+        let mut leap_secs = None;
+        let mut samples = 0;
+        let res = self.modem.listen(&[timeutc.cid, tp.cid], None, |frame| {
+            let received = Utc::now();
+            if stop_signal::requested() {
+                return false;
+            }
+            if frame.cid == tp.cid {
+                tp.from_bin(&frame.data);
+                if let Some(pulse) = tp.data.pulse_utc(leap_secs) {
+                    debug!(
+                        "next pulse {}, quantization error {:?} ps",
+                        pulse,
+                        tp.data.q_err()
+                    );
+                    refclock.pulse(pulse);
+                }
+            } else if frame.cid == timeutc.cid {
+                timeutc.from_bin(&frame.data);
+                let utc = match timeutc.data.utc() {
+                    Some(utc) => utc,
+                    None => {
+                        debug!("receiver time not resolved");
+                        return true;
+                    }
+                };
+                leap_secs = Some(gps_time::leap_seconds(timeutc.data.itow, utc));
+                if let Some(sample) = refclock.epoch(utc, received) {
+                    shm.write(&sample);
+                    samples += 1;
+                }
+            }
+            true
+        });
+
+        self.restore_message_rates(&rates)?;
+        res.map_err(|err| err.to_string())?;
+        info!("{} samples written", samples);
+        Ok(())
+    }
+
+    pub fn run_timemark(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        // unwrap must never fail here, as argument is checked by parser
+        let count = matches
+            .value_of("count")
+            .map(|count| count.parse::<u64>().unwrap());
+
+        // NAV-TIMEUTC provides the leap seconds for marks in GNSS time
+        let mut timeutc = UbxNavTimeUtc::create();
+        let mut tm2 = UbxTimTm2::create();
+        let rates = self
+            .enable_messages(&[timeutc.cid, tm2.cid])
+            .map_err(|err| format!("can't enable time mark messages ({})", err))?;
+
+        // Without count runs until stopped, signal ends listening so message rates are restored
+        stop_signal::install()?;
+        info!("waiting for time mark events");
+        let mut leap_secs = None;
+        let mut events = 0;
+        let res = self.modem.listen(&[timeutc.cid, tm2.cid], None, |frame| {
+            if stop_signal::requested() {
+                return false;
+            }
+            if frame.cid == timeutc.cid {
+                timeutc.from_bin(&frame.data);
+                if let Some(utc) = timeutc.data.utc() {
+                    leap_secs = Some(gps_time::leap_seconds(timeutc.data.itow, utc));
+                }
+            } else if frame.cid == tm2.cid {
+                tm2.from_bin(&frame.data);
+                println!("{}", MarkEvent::from_data(&tm2.data, leap_secs).to_json());
+                events += 1;
+            }
+            Some(events) != count
+        });
+
+        self.restore_message_rates(&rates)?;
+        res.map_err(|err| err.to_string())?;
+        info!("{} time mark events received", events);
+        Ok(())
+    }
+
+    // Waits for fully resolved receiver time with accuracy better than acc_ms
+    fn receiver_time(
+        &mut self,
+        clock: &dyn SystemClock,
+        acc_ms: u32,
+        timeout: u128,
+    ) -> Result<TimeSample, String> {
+        // NAV-PVT is used as well if already enabled
+        let cids = [UbxNavTimeUtc::create().cid, UbxNavPvt::create().cid];
+        let rates = self
+            .enable_messages(&cids[0..1])
+            .map_err(|err| format!("can't enable time messages ({})", err))?;
+
+        info!("waiting up to {} s for receiver time", timeout);
+        let mut sample = None;
+        let res =
+            self.modem.listen(
+                &cids,
+                Some(timeout * 1000),
+                |frame| match TimeSample::from_frame(frame, clock.now()) {
+                    Some(s) if s.accurate(acc_ms) => {
+                        sample = Some(s);
+                        false
+                    }
+                    Some(s) => {
+                        debug!("receiver time accuracy {} ns too low", s.acc_ns);
+                        true
+                    }
+                    None => {
+                        debug!("receiver time not resolved");
+                        true
+                    }
+                },
+            );
+
+        self.restore_message_rates(&rates)?;
+        res.map_err(|err| err.to_string())?;
+        sample.ok_or(format!("no valid receiver time within {} s", timeout))
+    }
+
+    pub fn run_record_sensors(&mut self, matches: &ArgMatches) -> Result<(), String> {
+        self.require(Feature::Imu)?;
+
+        // unwrap must never fail here, as arguments are required or have default values
+        let path = matches.value_of("csv").unwrap();
+        let duration = matches
+            .value_of("duration")
+            .unwrap()
+            .parse::<u128>()
+            .unwrap();
+
+        let file = File::create(path).map_err(|e| format!("can't create {} ({})", path, e))?;
+        let mut writer = std::io::BufWriter::new(file);
+        writeln!(writer, "message,time_tag,sensor,value,unit").map_err(|e| e.to_string())?;
+
+        let mut raw = UbxEsfRaw::new();
+        let mut meas = UbxEsfMeas::new(0);
+        let cids = [raw.cid, meas.cid];
+
+        // Enable sensor messages, previous rates are restored afterwards
+        let rates = self
+            .enable_messages(&cids)
+            .map_err(|err| format!("can't enable sensor messages ({})", err))?;
+
+        info!("recording sensor data for {} s to {}", duration, path);
+        let mut lines = 0;
+        let mut write_error = None;
+        let res = self.modem.listen(&cids, Some(duration * 1000), |frame| {
+            // Frames with unexpected layout are skipped, recording continues
+            let mut rows = Vec::new();
+            if frame.cid == raw.cid {
+                if !raw.load(&frame.data) {
+                    warn!(
+                        "{} with invalid length {}, skipping",
+                        raw.name,
+                        frame.data.len()
+                    );
+                }
+                for s in &raw.samples {
+                    rows.push(("ESF-RAW", s.s_ttag, SensorSample::from_u32(s.data)));
+                }
+            } else if frame.cid == meas.cid {
+                if !meas.load(&frame.data) {
+                    warn!(
+                        "{} with invalid length {}, skipping",
+                        meas.name,
+                        frame.data.len()
+                    );
+                }
+                for s in &meas.samples {
+                    rows.push(("ESF-MEAS", meas.header.time_tag, *s));
+                }
+            }
+
+            for (msg, time_tag, s) in rows {
+                let res = writeln!(
+                    writer,
+                    "{},{},{},{},{}",
+                    msg,
+                    time_tag,
+                    s.name(),
+                    s.value(),
+                    s.unit()
+                );
+                if let Err(e) = res {
+                    write_error = Some(e.to_string());
+                    return false;
+                }
+                lines += 1;
+            }
+            true
+        });
+
+        // Restore rates in any case, then report errors
+        self.restore_message_rates(&rates)?;
+        res.map_err(|err| err.to_string())?;
+        if let Some(err) = write_error {
+            return Err(format!("can't write {} ({})", path, err));
+        }
+        writer.flush().map_err(|e| e.to_string())?;
+
+        info!("{} sensor values recorded", lines);
+        Ok(())
+    }
+
+    // Outputs messages on UART1 at every navigation epoch, returns previous rates
+    fn enable_messages(&mut self, cids: &[UbxCID]) -> Result<Vec<(UbxCID, u8)>, String> {
+        let mut rates = Vec::new();
+        for cid in cids.iter() {
+            let rate = self
+                .modem
+                .message_rate(*cid, PortId::Uart1)
+                .map_err(|err| err.to_string())?;
+            rates.push((*cid, rate));
+        }
+        for cid in cids.iter() {
+            if let Err(err) = self.modem.set_message_rate(*cid, PortId::Uart1, 1) {
+                self.restore_message_rates(&rates)?;
+                return Err(err.to_string());
+            }
+        }
+        Ok(rates)
+    }
+
+    fn restore_message_rates(&mut self, rates: &[(UbxCID, u8)]) -> Result<(), String> {
+        for (cid, rate) in rates {
+            self.modem
+                .set_message_rate(*cid, PortId::Uart1, *rate)
+                .map_err(|err| format!("can't restore message rate ({})", err))?;
+        }
+        Ok(())
+    }
+
+    fn show_config(&mut self) -> Result<(), String> {
+        let navx5 = self.modem.navx5().map_err(|err| err.to_string())?;
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+
+        println!(
+            "AssistNow Auto:     {} (max orbit error {} m)",
+            on_off(navx5.aop_enabled()),
+            navx5.aop_orb_max_err
+        );
+        println!(
+            "Satellites:         min {}, max {}, min C/N0 {} dBHz",
+            navx5.min_svs, navx5.max_svs, navx5.min_cno
+        );
+        println!("Initial 3D fix:     {}", on_off(navx5.ini_fix_3d != 0));
+        println!("Week rollover:      {}", navx5.wkn_rollover);
+        println!("PPP:                {}", on_off(navx5.use_ppp != 0));
+
+        // TIMEPULSE2 and time mode only exist on timing modules
+        let mut pulses = vec![TimePulse::Tp1];
+        if self.firmware()?.supports(Feature::Timing) {
+            let tm = self.modem.time_mode().map_err(|err| err.to_string())?;
+            Self::print_time_mode(&tm);
+            pulses.push(TimePulse::Tp2);
+        }
+        for pulse in pulses {
+            let tp = self
+                .modem
+                .time_pulse(pulse)
+                .map_err(|err| err.to_string())?;
+            Self::print_time_pulse(pulse, &tp);
+        }
+
+        // Installation settings only exist on sensor fusion firmwares
+        if !self.firmware()?.supports(Feature::Imu) {
+            return Ok(());
+        }
+
+        let (angles, auto_align) = self.modem.imu_angles().map_err(|err| err.to_string())?;
+        let arms = self.modem.lever_arms().map_err(|err| err.to_string())?;
+        let wt = self.modem.wheel_ticks().map_err(|err| err.to_string())?;
+
+        println!(
+            "IMU angles:         yaw {:.2}, pitch {:.2}, roll {:.2}",
+            angles.yaw, angles.pitch, angles.roll
+        );
+        println!(
+            "Auto alignment:     {}",
+            if auto_align { "on" } else { "off" }
+        );
+        println!("Wheel ticks:");
+        println!("  factor           {:.6} m", wt.wt_factor as f64 / 1e6);
+        println!("  quant error      {:.6} m", wt.wt_quant_error as f64 / 1e6);
+        println!("  count max        {}", wt.wt_count_max);
+        println!("  latency          {} ms", wt.wt_latency);
+        println!(
+            "  dir polarity     {}",
+            if wt.flag(FLAG_DIR_PIN_POL) {
+                "inverted"
+            } else {
+                "normal"
+            }
+        );
+        println!(
+            "  source           {}",
+            if wt.flag(FLAG_USE_WT_SPEED) {
+                "speed"
+            } else {
+                "ticks"
+            }
+        );
+        println!("Lever arms [m]:");
+        for arm in &arms.arms {
+            let name = match arm.armtype() {
+                Some(armtype) => String::from(armtype.name()),
+                None => format!("type {}", arm.leverarm_type),
+            };
+            println!(
+                "  {:<16} {:.2};{:.2};{:.2}",
+                name,
+                arm.leverarm_x as f32 / 100.0,
+                arm.leverarm_y as f32 / 100.0,
+                arm.leverarm_z as f32 / 100.0
+            );
+        }
+
+        Ok(())
+    }
+
+    fn print_time_mode(tm: &DataCfgTmode2) {
+        println!("Time mode:          {}", tm.mode_name());
+        println!(
+            "  survey-in        min {} s, accuracy {:.3} m",
+            tm.svin_min_dur,
+            tm.svin_acc_limit as f64 / 1000.0
+        );
+        if tm.lla() {
+            println!(
+                "  fixed position   LLH {:.7};{:.7};{:.2}, accuracy {:.3} m",
+                tm.ecef_x_or_lat as f64 * 1e-7,
+                tm.ecef_y_or_lon as f64 * 1e-7,
+                tm.ecef_z_or_alt as f64 / 100.0,
+                tm.fixed_pos_acc as f64 / 1000.0
+            );
+        } else {
+            println!(
+                "  fixed position   ECEF {:.2};{:.2};{:.2}, accuracy {:.3} m",
+                tm.ecef_x_or_lat as f64 / 100.0,
+                tm.ecef_y_or_lon as f64 / 100.0,
+                tm.ecef_z_or_alt as f64 / 100.0,
+                tm.fixed_pos_acc as f64 / 1000.0
+            );
+        }
+    }
+
+    fn print_time_pulse(pulse: TimePulse, tp: &DataCfgTp5) {
+        let freq_period = |value: u32| {
+            if tp.flag(FLAG_IS_FREQ) {
+                format!("freq {} Hz", value)
+            } else {
+                format!("period {} us", value)
+            }
+        };
+        let len_ratio = |value: u32| {
+            if tp.flag(FLAG_IS_LENGTH) {
+                format!("length {} us", value)
+            } else {
+                format!("duty {:.1} %", value as f64 / 4_294_967_296.0 * 100.0)
+            }
+        };
+
+        println!("{}:", pulse.name());
+        println!(
+            "  active           {}",
+            if tp.flag(FLAG_ACTIVE) { "yes" } else { "no" }
+        );
+        println!(
+            "  unlocked         {}, {}",
+            freq_period(tp.freq_period),
+            len_ratio(tp.pulse_len_ratio)
+        );
+        if tp.flag(FLAG_LOCKED_OTHER_SET) {
+            println!(
+                "  locked           {}, {}",
+                freq_period(tp.freq_period_lock),
+                len_ratio(tp.pulse_len_ratio_lock)
+            );
+        }
+        println!(
+            "  sync to GNSS     {}",
+            if tp.flag(FLAG_LOCK_GNSS_FREQ) {
+                "on"
+            } else {
+                "off"
+            }
+        );
+        println!(
+            "  polarity         {}",
+            if tp.flag(FLAG_POLARITY) {
+                "rising"
+            } else {
+                "falling"
+            }
+        );
+        println!(
+            "  alignment        {}",
+            if tp.flag(FLAG_ALIGN_TO_TOW) {
+                tp.grid_name()
+            } else {
+                "none"
+            }
+        );
+        println!(
+            "  delays           cable {} ns, rf {} ns, user {} ns",
+            tp.ant_cable_delay, tp.rf_group_delay, tp.user_config_delay
+        );
+    }
+
+    fn print_imu_status(status: &UbxEsfStatus, alg: &DataEsfAlg, att: Option<&DataNavAtt>) {
+        println!("Fusion mode:        {}", status.fusion_mode_name());
+        println!(
+            "Alignment:          {} (auto alignment {})",
+            alg.status_name(),
+            if alg.auto_alignment() { "on" } else { "off" }
+        );
+        println!(
+            "Angles:             yaw {:.2}, pitch {:.2}, roll {:.2}",
+            alg.yaw as f32 / 100.0,
+            alg.pitch as f32 / 100.0,
+            alg.roll as f32 / 100.0
+        );
+        if alg.errors != 0 {
+            println!("Alignment errors:   {}", alg.errors_to_string());
+        }
+        match att {
+            Some(att) => println!(
+                "Attitude:           roll {:.2} ±{:.2}, pitch {:.2} ±{:.2}, heading {:.2} ±{:.2}",
+                att.roll as f64 * 1e-5,
+                att.acc_roll as f64 * 1e-5,
+                att.pitch as f64 * 1e-5,
+                att.acc_pitch as f64 * 1e-5,
+                att.heading as f64 * 1e-5,
+                att.acc_heading as f64 * 1e-5
+            ),
+            None => println!("Attitude:           n/a"),
+        }
+
+        println!("Sensors:");
+        for sensor in &status.sensors {
+            println!(
+                "  {:<12} {:<14} {:>3} Hz  {}{}  {}",
+                sensor.type_name(),
+                sensor.calib_status_name(),
+                sensor.freq,
+                if sensor.used() { "used" } else { "----" },
+                if sensor.ready() { ", ready" } else { "" },
+                sensor.faults_to_string()
+            );
+        }
+    }
+
+    fn configure_ports(&mut self, ports: &[PortConfig]) -> Result<(), String> {
+        for port in ports {
+            if port.in_proto_mask.is_some() || port.out_proto_mask.is_some() {
+                self.modem
+                    .set_port_protocols(port.port, port.in_proto_mask, port.out_proto_mask)
+                    .map_err(|err| format!("can't set protocols of {:?} ({})", port.port, err))?;
+            }
+
+            if let Some(baudrate) = port.baudrate {
+                self.modem
+                    .set_port_baudrate(port.port, baudrate)
+                    .map_err(|err| format!("can't set baudrate of {:?} ({})", port.port, err))?;
+            }
+        }
         Ok(())
     }
 
@@ -183,6 +1341,13 @@ impl GnssMgr {
                 .map_err(|err| err.to_string())?;
         }
 
+        if let Some(rate) = config.hnr_rate {
+            self.require(Feature::Hnr)?;
+            self.modem
+                .set_hnr_rate(rate as u8)
+                .map_err(|err| err.to_string())?;
+        }
+
         // Set Satellite systems
         if let Some(systems) = &config.systems {
             match self.modem.set_systems(systems) {
@@ -195,6 +1360,27 @@ impl GnssMgr {
             }
         }
 
+        // Navigation expert settings incl. AssistNow Autonomous
+        if let Some(navx5) = &config.navx5 {
+            match self.modem.set_navx5(navx5) {
+                Ok(_) => (),
+                Err(Error::ModemNAK) if navx5.ppp == Some(true) => {
+                    return Err("PPP not supported by firmware".to_string());
+                }
+                Err(e) => return Err(format!("can't set navigation expert settings ({})", e)),
+            }
+        }
+
+        // IMU settings require sensor fusion firmware
+        if config.imu_angles.is_some()
+            || config.auto_align.is_some()
+            || config.vrp2antenna.is_some()
+            || config.vrp2imu.is_some()
+            || config.wheel_ticks.is_some()
+        {
+            self.require(Feature::Imu)?;
+        }
+
         // IMU Orientation
         if let Some(angles) = config.imu_angles {
             self.modem
@@ -202,6 +1388,13 @@ impl GnssMgr {
                 .map_err(|err| err.to_string())?;
         }
 
+        // Automatic IMU mount alignment
+        if let Some(enable) = config.auto_align {
+            self.modem
+                .set_auto_alignment(enable)
+                .map_err(|err| err.to_string())?;
+        }
+
         // Lever Arms
         if let Some(xyz) = config.vrp2antenna {
             self.modem
@@ -215,14 +1408,106 @@ impl GnssMgr {
                 .map_err(|err| err.to_string())?;
         }
 
+        // Wheel tick sensor
+        if let Some(wt) = &config.wheel_ticks {
+            self.modem
+                .set_wheel_ticks(wt)
+                .map_err(|err| format!("can't set wheel tick settings ({})", err))?;
+        }
+
+        // Port protocols and baudrates
+        if let Some(ports) = &config.ports {
+            self.configure_ports(ports)?;
+        }
+
+        // Time pulses, TIMEPULSE2 only exists on timing modules
+        if let Some(pulses) = &config.timepulses {
+            for tp in pulses {
+                if tp.pulse == TimePulse::Tp2 {
+                    self.require(Feature::Timing)?;
+                }
+                self.modem
+                    .set_time_pulse(tp)
+                    .map_err(|err| format!("can't set {} settings ({})", tp.pulse.name(), err))?;
+            }
+        }
+
+        // Time mode of stationary timing receivers
+        if let Some(tm) = &config.time_mode {
+            self.require(Feature::Timing)?;
+            self.modem
+                .set_time_mode(tm)
+                .map_err(|err| format!("can't set time mode ({})", err))?;
+        }
+
+        // Message output rates on UART1, each setting is read back
+        if let Some(messages) = &config.messages {
+            for msg in messages {
+                self.modem
+                    .set_message_rate(msg.cid, PortId::Uart1, msg.rate)
+                    .map_err(|err| format!("can't set rate of {} ({})", msg.name, err))?;
+            }
+        }
+
         Ok(())
     }
 
-    fn write_runfile(path: &Path, info: &HashMap<&str, String>) -> Result<(), String> {
-        let deprecated = if info["fw_ver"] != CURRENT_FW_VER {
-            " (Deprecated)"
-        } else {
-            ""
+    // Gets firmware information, modem is queried on first use
+    fn firmware(&mut self) -> Result<&FwInfo, String> {
+        if self.firmware.is_none() {
+            let mut info: HashMap<&str, String> = HashMap::new();
+            self.modem
+                .version(&mut info)
+                .map_err(|e| format!("can't get modem information ({})", e))?;
+            self.firmware = Some(FwInfo::lookup(&info["model"], &info["fw_ver"]));
+        }
+
+        Ok(self.firmware.as_ref().unwrap())
+    }
+
+    // Checks that modem firmware supports a feature, e.g. IMU for sensor fusion
+    fn require(&mut self, feature: Feature) -> Result<(), String> {
+        let fw = self.firmware()?;
+        if !fw.supports(feature) {
+            return Err(format!(
+                "firmware {} of {} does not support {} feature",
+                fw.fw_ver,
+                fw.model,
+                feature.name()
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_firmware(fw: &FwInfo) {
+        let recommended = match &fw.recommended {
+            Some(ver) => format!(", recommended is {}", ver),
+            None => String::new(),
+        };
+
+        match fw.status {
+            FwStatus::Recommended => debug!("firmware {} is recommended version", fw.fw_ver),
+            FwStatus::Deprecated => warn!(
+                "firmware {} of {} is deprecated{}",
+                fw.fw_ver, fw.model, recommended
+            ),
+            FwStatus::KnownBad => warn!(
+                "firmware {} of {} is known to be bad, update required{}",
+                fw.fw_ver, fw.model, recommended
+            ),
+            FwStatus::Unknown => warn!(
+                "firmware {} of {} is not tested{}",
+                fw.fw_ver, fw.model, recommended
+            ),
+        }
+    }
+
+    fn write_runfile(path: &Path, info: &HashMap<&str, String>, fw: &FwInfo) -> Result<(), String> {
+        let status = match fw.status {
+            FwStatus::Recommended => "",
+            FwStatus::Deprecated => " (Deprecated)",
+            FwStatus::KnownBad => " (Known bad)",
+            FwStatus::Unknown => " (Untested)",
         };
 
         let text = format!(
@@ -237,7 +1522,7 @@ impl GnssMgr {
             info["vendor"],
             info["model"],
             info["fw_ver"],
-            deprecated,
+            status,
             info["protocol"],
             info["systems"],
             info["augmentation"],
@@ -245,32 +1530,286 @@ impl GnssMgr {
             info["hw_ver"],
         );
 
-        // let path = Path::new(path);
-        let parent = path.parent().unwrap();
+        Self::write_atomic(path, &text)
+    }
 
-        fs::create_dir_all(parent)
-            .map_err(|_err| format!("can't create GNSS run file folder {}", parent.display()))?;
+    fn write_runfile_vars(
+        path: &Path,
+        info: &HashMap<&str, String>,
+        fw: &FwInfo,
+    ) -> Result<(), String> {
+        // Machine readable variant of run file, can be sourced by shell scripts.
+        // Values are single-quoted, so $, ` and \ in version strings are not expanded.
+        let keys = [
+            "vendor",
+            "model",
+            "fw_ver",
+            "protocol",
+            "systems",
+            "augmentation",
+            "sw_ver",
+            "hw_ver",
+        ];
 
-        let mut file = File::create(&path)
-            .map_err(|_err| format!("can't create GNSS run file {}", path.display()))?;
-        file.write_all(text.as_bytes())
-            .map_err(|_err| "can't write GNSS run file".to_string())?;
+        let mut text = String::new();
+        for key in keys.iter() {
+            let value = Self::shell_quote(&info[key]);
+            text.push_str(&format!("{}={}\n", key.to_uppercase(), value));
+        }
+        let status = Self::shell_quote(&fw.status.to_string());
+        text.push_str(&format!("FW_STATUS={}\n", status));
+        let features = Self::shell_quote(&fw.features_to_string());
+        text.push_str(&format!("FEATURES={}\n", features));
+
+        Self::write_atomic(path, &text)
+    }
+
+    // Single quotes, embedded single quotes are closed, escaped and reopened
+    fn shell_quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+
+    // Readers never see partially written run files
+    fn write_atomic(path: &Path, text: &str) -> Result<(), String> {
+        atomic_file::write(path, text.as_bytes())
+            .map_err(|err| format!("can't write GNSS run file {} ({})", path.display(), err))
+    }
 
+    // Navigation database is stored as received, a concatenation of UBX-MGA-DBD frames
+    fn save_database(&mut self, path: &str) -> Result<(), String> {
+        let frames = self
+            .modem
+            .navigation_database()
+            .map_err(|err| format!("can't read navigation database ({})", err))?;
+
+        let mut data = Vec::new();
+        for frame in &frames {
+            data.extend(UbxFrame::bytes(frame.cid, &frame.data));
+        }
+
+        atomic_file::write(Path::new(path), &data)
+            .map_err(|e| format!("can't write {} ({})", path, e))?;
+
+        info!("{} database messages, {} bytes", frames.len(), data.len());
         Ok(())
     }
 
-    fn build_runfile_path(path: &str) -> PathBuf {
-        // Take devicename of form /dev/<name> to build /run/gnss/<name>.config
-        let path = &path.replace("/dev/", "/run/gnss/");
-        let mut path = PathBuf::from(path);
-        path.set_extension("config");
-        path
+    fn build_statefile_path(&self) -> PathBuf {
+        let name = Self::stable_name(Path::new(&self.device_name), Path::new("/dev"));
+        self.state_dir.join(format!("{}.pos", name))
+    }
+
+    // Stores last valid position, failures must not prevent saving receiver state
+    fn save_position(&mut self) {
+        let pvt = match self.modem.nav_pvt() {
+            Ok(pvt) => pvt,
+            Err(err) => {
+                warn!("can't get position ({})", err);
+                return;
+            }
+        };
+        if !pvt.position_valid() {
+            info!("no valid position, keeping last known position");
+            return;
+        }
+
+        let pos = LastPosition {
+            time: pvt.utc().unwrap_or_else(Utc::now),
+            lat: pvt.lat,
+            lon: pvt.lon,
+            height: pvt.height,
+            acc: pvt.h_acc,
+        };
+        let path = self.build_statefile_path();
+        match pos.save(&path) {
+            Ok(_) => info!("last position stored in {}", path.display()),
+            Err(err) => warn!("{}", err),
+        }
     }
 
-    fn build_configfile_path(path: &str) -> PathBuf {
-        let path = &path.replace("/dev/", "/etc/gnss/");
-        let mut path = PathBuf::from(path);
-        path.set_extension("conf");
-        path
+    // Injects last known position if recent and accurate enough
+    fn restore_position(&mut self) {
+        let path = self.build_statefile_path();
+        let pos = match LastPosition::load(&path) {
+            Ok(pos) => pos,
+            Err(err) => {
+                info!("no last position available ({})", err);
+                return;
+            }
+        };
+        if let Err(reason) = pos.check(Utc::now()) {
+            info!("not using last position, {}", reason);
+            return;
+        }
+
+        match self
+            .modem
+            .set_assistance_position(pos.lat, pos.lon, pos.height, pos.acc)
+        {
+            Ok(_) => info!("last position injected"),
+            Err(err) => warn!("can't inject last position ({})", err),
+        }
+    }
+
+    fn build_configfile_path(&self) -> PathBuf {
+        let name = Self::stable_name(Path::new(&self.device_name), Path::new("/dev"));
+        self.config_dir.join(format!("{}.conf", name))
+    }
+
+    /*
+    Determine a stable name for the device, used for run and config files
+
+    - symlinks such as /dev/gnss0 or /dev/serial/by-id/<id> keep their own name
+    - for other devices (e.g. /dev/ttyS3) a symlink in dev_dir pointing to the
+      device is searched, e.g. /dev/gnss0 -> ttyS3
+    - otherwise the device file name is used
+    */
+    fn stable_name(device: &Path, dev_dir: &Path) -> String {
+        let file_name = |p: &Path| match p.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => String::from("gnss"),
+        };
+
+        let is_link = match fs::symlink_metadata(device) {
+            Ok(meta) => meta.file_type().is_symlink(),
+            Err(_) => false,
+        };
+        if is_link {
+            return file_name(device);
+        }
+
+        if let (Ok(target), Ok(entries)) = (fs::canonicalize(device), fs::read_dir(dev_dir)) {
+            let mut links: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| match fs::symlink_metadata(p) {
+                    Ok(meta) => meta.file_type().is_symlink(),
+                    Err(_) => false,
+                })
+                .filter(|p| fs::canonicalize(p).ok() == Some(target.clone()))
+                .collect();
+
+            // Make result independent of directory order
+            links.sort();
+            if let Some(link) = links.first() {
+                debug!("using name of {} for {}", link.display(), device.display());
+                return file_name(link);
+            }
+        }
+
+        file_name(device)
+    }
+}
+
+#[cfg(test)]
+mod paths {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gnss-mgr-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn plain_device() {
+        let dir = test_dir("plain");
+        let dev = dir.join("ttyS3");
+        File::create(&dev).unwrap();
+
+        assert_eq!(GnssMgr::stable_name(&dev, &dir), "ttyS3");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn symlinked_device() {
+        let dir = test_dir("symlink");
+        let dev = dir.join("ttyS3");
+        File::create(&dev).unwrap();
+        symlink(&dev, dir.join("gnss0")).unwrap();
+
+        // Link keeps its name, device is resolved to link name
+        assert_eq!(GnssMgr::stable_name(&dir.join("gnss0"), &dir), "gnss0");
+        assert_eq!(GnssMgr::stable_name(&dev, &dir), "gnss0");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn by_id_device() {
+        let dir = test_dir("byid");
+        let dev = dir.join("ttyACM0");
+        File::create(&dev).unwrap();
+        let by_id = dir.join("serial").join("by-id");
+        fs::create_dir_all(&by_id).unwrap();
+        let link = by_id.join("usb-u-blox_AG_-_www.u-blox.com_u-blox_GNSS_receiver-if00");
+        symlink(&dev, &link).unwrap();
+
+        assert_eq!(
+            GnssMgr::stable_name(&link, &dir),
+            "usb-u-blox_AG_-_www.u-blox.com_u-blox_GNSS_receiver-if00"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn runfiles() {
+        let dir = test_dir("runfiles");
+        let mut info: HashMap<&str, String> = HashMap::new();
+        info.insert("vendor", String::from("ublox"));
+        info.insert("model", String::from("NEO-M8L-0"));
+        info.insert("fw_ver", String::from("ADR 4.31"));
+        info.insert("protocol", String::from("19.20"));
+        info.insert("systems", String::from("GPS;GLO;GAL;BDS"));
+        info.insert("augmentation", String::from("SBAS;IMES;QZSS"));
+        info.insert("sw_ver", String::from("EXT CORE 3.01 (1ec93f)"));
+        info.insert("hw_ver", String::from("00080000"));
+
+        let fw = FwInfo::lookup(&info["model"], &info["fw_ver"]);
+
+        let path = dir.join("run").join("gnss0.config");
+        GnssMgr::write_runfile(&path, &info, &fw).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("Firmware:                           ADR 4.31\n"));
+
+        let path = dir.join("run").join("gnss0.env");
+        GnssMgr::write_runfile_vars(&path, &info, &fw).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("VENDOR='ublox'\n"));
+        assert!(text.contains("FW_VER='ADR 4.31'\n"));
+        assert!(text.contains("SW_VER='EXT CORE 3.01 (1ec93f)'\n"));
+        assert!(text.contains("FW_STATUS='recommended'\n"));
+        assert!(text.contains("FEATURES='IMU;HNR'\n"));
+
+        // Deprecated firmware is marked in human readable file
+        let fw = FwInfo {
+            status: FwStatus::Deprecated,
+            ..fw
+        };
+        let mut info = info.clone();
+        info.insert("fw_ver", String::from("ADR 4.21"));
+        let path = dir.join("run").join("gnss0.config");
+        GnssMgr::write_runfile(&path, &info, &fw).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("Firmware:                           ADR 4.21 (Deprecated)\n"));
+
+        // No temporary files left behind
+        assert_eq!(fs::read_dir(dir.join("run")).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn shell_quote() {
+        assert_eq!(GnssMgr::shell_quote("ADR 4.31"), "'ADR 4.31'");
+        assert_eq!(
+            GnssMgr::shell_quote("$(reboot) `id` \\"),
+            "'$(reboot) `id` \\'"
+        );
+        assert_eq!(GnssMgr::shell_quote("it's"), "'it'\\''s'");
     }
 }
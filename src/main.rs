@@ -1,6 +1,14 @@
+mod atomic_file;
+mod clock;
 mod config_file;
+mod feed;
+mod firmware;
 mod gnss_mgr;
 mod neo_m8;
+mod ntp_shm;
+mod state;
+mod stop_signal;
+mod timemark;
 mod ubxlib;
 
 use std::env;
@@ -24,7 +32,7 @@ fn main() {
 
     let res = run_app(&matches);
     let ec = match res {
-        Ok(_) => 0,
+        Ok(ec) => ec,
         Err(err) => {
             eprintln!("error: {}", err);
             1
@@ -33,12 +41,12 @@ fn main() {
     std::process::exit(ec);
 }
 
-fn run_app(matches: &ArgMatches) -> Result<(), String> {
+fn run_app(matches: &ArgMatches) -> Result<i32, String> {
     // unwrap must never fail here, as argument is checked by parser already
     let mut device_name: String = matches.value_of("device").unwrap().to_string();
 
-    // complete devicename if it was given in short hand form, e.g. gnss instead of /dev/gnss0
-    if !device_name.starts_with("/dev/") {
+    // complete devicename if it was given in short hand form, e.g. gnss0 instead of /dev/gnss0
+    if !device_name.starts_with('/') {
         device_name = format!("/dev/{}", device_name);
     }
 
@@ -48,21 +56,43 @@ fn run_app(matches: &ArgMatches) -> Result<(), String> {
     // Create GNSS Manager on specified device
     let mut gnss = GnssMgr::new(&device_name);
 
-    // The "init" command checks the current bitrate and changes to 115200 if required.
-    // all other subcommand use the modem at 115200.
-    let bitrate = match matches.subcommand() {
-        ("init", Some(_)) => None,
-        _ => Some(115200 as u32),
+    // unwrap must never fail here, as arguments have default values
+    gnss.set_run_dir(matches.value_of("run-dir").unwrap());
+    gnss.set_config_dir(matches.value_of("config-dir").unwrap());
+    gnss.set_state_dir(matches.value_of("state-dir").unwrap());
+
+    // The "init" command checks the current bitrate and changes to the desired bitrate
+    // (default 115200) if required. All other subcommands use the modem at this bitrate.
+    // unwrap must never fail here, as argument has a default value and is checked by parser
+    let bitrate = matches
+        .value_of("baudrate")
+        .unwrap()
+        .parse::<u32>()
+        .unwrap();
+    let detection = match matches.subcommand() {
+        ("init", Some(m)) => m.value_of("detection"),
+        _ => None,
     };
 
-    gnss.prepare_port(bitrate)?;
+    gnss.prepare_port(bitrate, detection)?;
 
-    // Execute desired command, returns Result directly
+    // Execute desired command, most commands only report success or failure,
+    // sos also reports the backup state as exit code
     match matches.subcommand() {
-        ("init", Some(m)) => gnss.run_init(m),
-        ("config", Some(m)) => gnss.run_config(m),
-        ("control", Some(m)) => gnss.run_control(m),
+        ("init", Some(m)) => gnss.run_init(m).map(|_| 0),
+        ("config", Some(m)) => gnss.run_config(m).map(|_| 0),
+        ("control", Some(m)) => gnss.run_control(m).map(|_| 0),
         ("sos", Some(m)) => gnss.run_sos(m),
+        ("assist", Some(m)) => gnss.run_assist(m).map(|_| 0),
+        ("imu-status", Some(m)) => gnss.run_imu_status(m).map(|_| 0),
+        ("imu-align", Some(m)) => gnss.run_imu_align(m).map(|_| 0),
+        ("feed", Some(m)) => gnss.run_feed(m).map(|_| 0),
+        ("monitor", Some(m)) => gnss.run_monitor(m).map(|_| 0),
+        ("record-sensors", Some(m)) => gnss.run_record_sensors(m).map(|_| 0),
+        ("time", Some(m)) => gnss.run_time(m).map(|_| 0),
+        ("ntp-shm", Some(m)) => gnss.run_ntp_shm(m).map(|_| 0),
+        ("survey", Some(m)) => gnss.run_survey(m).map(|_| 0),
+        ("timemark", Some(m)) => gnss.run_timemark(m).map(|_| 0),
         _ => Err("Unknown command".to_string()),
     }
 }
@@ -80,13 +110,53 @@ fn setup_arg_parse() -> App<'static, 'static> {
             .short("q")
             .conflicts_with("verbose")
             .help("Be quiet, only show warnings and errors"))
+        .arg(Arg::with_name("baudrate")
+            .short("b")
+            .long("baudrate")
+            .value_name("BAUDRATE")
+            .default_value("115200")
+            .possible_values(&["4800", "9600", "19200", "38400", "57600", "115200",
+                               "230400", "460800", "921600"])
+            .help("Bitrate used to communicate with modem, init command changes modem to this bitrate"))
+        .arg(Arg::with_name("run-dir")
+            .long("run-dir")
+            .value_name("DIR")
+            .env("GNSS_MGR_RUN_DIR")
+            .default_value("/run/gnss")
+            .help("Folder for run files"))
+        .arg(Arg::with_name("config-dir")
+            .long("config-dir")
+            .value_name("DIR")
+            .env("GNSS_MGR_CONFIG_DIR")
+            .default_value("/etc/gnss")
+            .help("Folder for default configuration files"))
+        .arg(Arg::with_name("state-dir")
+            .long("state-dir")
+            .value_name("DIR")
+            .env("GNSS_MGR_STATE_DIR")
+            .default_value("/var/lib/gnss")
+            .help("Folder for receiver state kept across power cycles, e.g. last position"))
 
-            .arg(Arg::with_name("device")
+        .arg(Arg::with_name("device")
             .required(true)
             .help("local serial device to which GNSS modem is connected (e.g. /dev/gnss0)"))
 
         .subcommand(SubCommand::with_name("init")
-            .about("Initializes GNSS, sets bitrate"))
+            .about("Initializes GNSS, sets bitrate")
+            .arg(Arg::with_name("detection")
+                .short("d")
+                .long("detection")
+                .value_name("MODE")
+                .default_value("auto")
+                .possible_values(&["auto", "passive", "active"])
+                .help("Bitrate detection: listen to receiver output (passive), poll receiver (active) or both"))
+            .arg(Arg::with_name("port")
+                .short("p")
+                .long("port")
+                .value_name("SETTING")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Port protocols or baudrate as in [ports] section, e.g. usb-out=ubx;nmea or uart2-baudrate=38400")))
 
         .subcommand(SubCommand::with_name("config")
             .about("Configures GNSS modem")
@@ -94,21 +164,245 @@ fn setup_arg_parse() -> App<'static, 'static> {
                 .short("f")
                 .long("file")
                 .value_name("CONFIGFILE")
-                .help("Path to configuration file")))
+                .help("Path to configuration file"))
+            .arg(Arg::with_name("show")
+                .short("s")
+                .long("show")
+                .conflicts_with("configfile")
+                .help("Shows installation settings of modem instead of configuring it")))
 
         .subcommand(SubCommand::with_name("control")
             .about("Performs GNSS modem control function")
             .arg(Arg::with_name("action")
                 .required(true)
-                .possible_values(&["cold-start", "persist", "factory-reset"])
-                .help("Selects action to perform")))
+                .possible_values(&["cold-start", "persist", "factory-reset", "reset-fusion"])
+                .help("Selects action to perform"))
+            .arg(Arg::with_name("clear-calibration")
+                .short("c")
+                .long("clear-calibration")
+                .help("Also clears sensor calibration in battery backed RAM (reset-fusion only)")))
+
+        .subcommand(SubCommand::with_name("assist")
+            .about("Sends assistance data to GNSS modem")
+            .arg(Arg::with_name("action")
+                .required(true)
+                .possible_values(&["time", "load"])
+                .help("Selects assistance data to send"))
+            .arg(Arg::with_name("file")
+                .required_if("action", "load")
+                .help("File with UBX-MGA messages, e.g. AssistNow Offline or Online data (load only)"))
+            .arg(Arg::with_name("no-ack")
+                .long("no-ack")
+                .help("Sends messages without waiting for UBX-MGA-ACK (load only)"))
+            .arg(Arg::with_name("accuracy")
+                .short("a")
+                .long("accuracy")
+                .value_name("SECONDS")
+                .default_value("10")
+                .validator(|v| match v.parse::<f64>() {
+                    Ok(a) if a > 0.0 && a < 65536.0 => Ok(()),
+                    _ => Err("accuracy must be between 0 and 65535 s".to_string()),
+                })
+                .help("Accuracy of host time"))
+            .arg(Arg::with_name("leap-seconds")
+                .long("leap-seconds")
+                .value_name("SECONDS")
+                .validator(|v| v.parse::<i8>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Number of GPS to UTC leap seconds [default: from host leap-seconds.list]"))
+            .arg(Arg::with_name("extint")
+                .long("extint")
+                .value_name("PIN")
+                .possible_values(&["0", "1"])
+                .help("Time refers to next pulse on EXTINT pin instead of message receipt")))
 
         .subcommand(SubCommand::with_name("sos")
             .about("Save on shutdown operations")
             .arg(Arg::with_name("action")
                 .required(true)
-                .possible_values(&["save", "clear"])
-                .help("Selects sos operation to perform")));
+                .possible_values(&["save", "clear", "restore", "status"])
+                .help("Selects sos operation to perform"))
+            .arg(Arg::with_name("host")
+                .long("host")
+                .value_name("FILE")
+                .required_if("action", "restore")
+                .help("Keeps navigation database (UBX-MGA-DBD) in host file instead of receiver flash (save, restore)")))
+
+        .subcommand(SubCommand::with_name("imu-status")
+            .about("Shows sensor fusion and IMU alignment status")
+            .arg(Arg::with_name("wait")
+                .short("w")
+                .long("wait")
+                .value_name("STATE")
+                .possible_values(&["fusion", "calibrated", "aligned"])
+                .help("Waits until sensor fusion reaches state"))
+            .arg(Arg::with_name("timeout")
+                .short("t")
+                .long("timeout")
+                .value_name("SECONDS")
+                .default_value("300")
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Maximum time to wait for state")))
+
+        .subcommand(SubCommand::with_name("imu-align")
+            .about("Stores estimated IMU alignment as fixed angles in configuration file")
+            .arg(Arg::with_name("configfile")
+                .short("f")
+                .long("file")
+                .value_name("CONFIGFILE")
+                .help("Path to configuration file"))
+            .arg(Arg::with_name("force")
+                .long("force")
+                .help("Store angles even if alignment has not completed")))
+
+        .subcommand(SubCommand::with_name("feed")
+            .about("Forwards wheel ticks and speed from stdin or CAN to sensor fusion")
+            .arg(Arg::with_name("interface")
+                .short("i")
+                .long("interface")
+                .value_name("CANIF")
+                .help("SocketCAN interface to read from (e.g. can0), reads stdin if not given"))
+            .arg(Arg::with_name("speed-id")
+                .long("speed-id")
+                .value_name("ID")
+                .default_value("0x3e8")
+                .validator(|v| feed::parse_can_id(&v).map(|_| ()))
+                .help("CAN id of speed frame"))
+            .arg(Arg::with_name("ticks-id")
+                .long("ticks-id")
+                .value_name("ID")
+                .default_value("0x3e9")
+                .validator(|v| feed::parse_can_id(&v).map(|_| ()))
+                .help("CAN id of wheel tick frame"))
+            .arg(Arg::with_name("rate")
+                .short("r")
+                .long("rate")
+                .value_name("HZ")
+                .default_value("10")
+                .validator(|v| match v.parse::<u32>() {
+                    Ok(1..=50) => Ok(()),
+                    _ => Err(String::from("rate must be 1 to 50 Hz")),
+                })
+                .help("Maximum rate measurements are sent to the receiver")))
+
+        .subcommand(SubCommand::with_name("monitor")
+            .about("Shows fused solution (UBX-HNR-PVT, UBX-HNR-ATT, UBX-ESF-INS, UBX-NAV-ATT)")
+            .arg(Arg::with_name("count")
+                .short("n")
+                .long("count")
+                .value_name("COUNT")
+                .default_value("0")
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Stops after number of messages, 0 for no limit"))
+            .arg(Arg::with_name("timeout")
+                .short("t")
+                .long("timeout")
+                .value_name("SECONDS")
+                .default_value("10")
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Stops after time")))
+
+        .subcommand(SubCommand::with_name("record-sensors")
+            .about("Records raw sensor data (UBX-ESF-RAW, UBX-ESF-MEAS) to CSV file")
+            .arg(Arg::with_name("csv")
+                .long("csv")
+                .value_name("FILE")
+                .required(true)
+                .help("Path of CSV file to write"))
+            .arg(Arg::with_name("duration")
+                .short("d")
+                .long("duration")
+                .value_name("SECONDS")
+                .default_value("60")
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Recording time")))
+
+        .subcommand(SubCommand::with_name("time")
+            .about("Sets system clock from receiver time (UBX-NAV-TIMEUTC, UBX-NAV-PVT)")
+            .arg(Arg::with_name("action")
+                .required(true)
+                .possible_values(&["sync"])
+                .help("Selects time operation to perform"))
+            .arg(Arg::with_name("check")
+                .long("check")
+                .help("Only reports offset of system clock, doesn't set it"))
+            .arg(Arg::with_name("accuracy")
+                .short("a")
+                .long("accuracy")
+                .value_name("MS")
+                .default_value("100")
+                .validator(|v| match v.parse::<u32>() {
+                    Ok(a) if a > 0 && a <= 60_000 => Ok(()),
+                    _ => Err("accuracy must be between 1 and 60000 ms".to_string()),
+                })
+                .help("Required accuracy of receiver time"))
+            .arg(Arg::with_name("timeout")
+                .short("t")
+                .long("timeout")
+                .value_name("SECONDS")
+                .default_value("300")
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Maximum time to wait for valid receiver time")))
+
+        .subcommand(SubCommand::with_name("ntp-shm")
+            .about("Writes receiver time to ntpd/chrony shared memory refclock (UBX-NAV-TIMEUTC, UBX-TIM-TP)")
+            .arg(Arg::with_name("unit")
+                .short("u")
+                .long("unit")
+                .value_name("UNIT")
+                .default_value("0")
+                .validator(|v| v.parse::<u8>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("SHM unit, segment key is 0x4e545030 + unit")))
+
+        .subcommand(SubCommand::with_name("survey")
+            .about("Determines position of stationary timing receiver by survey-in (UBX-CFG-TMODE2, UBX-TIM-SVIN)")
+            .arg(Arg::with_name("min-duration")
+                .short("d")
+                .long("min-duration")
+                .value_name("SECONDS")
+                .default_value("300")
+                .validator(|v| match v.parse::<u32>() {
+                    Ok(d) if d > 0 => Ok(()),
+                    _ => Err("minimum duration must be at least 1 s".to_string()),
+                })
+                .help("Minimum survey-in duration"))
+            .arg(Arg::with_name("accuracy")
+                .short("a")
+                .long("accuracy")
+                .value_name("METERS")
+                .default_value("2.0")
+                .validator(|v| match v.parse::<f64>() {
+                    Ok(a) if a > 0.0 && a <= 1000.0 => Ok(()),
+                    _ => Err("accuracy must be between 0 and 1000 m".to_string()),
+                })
+                .help("Required position accuracy"))
+            .arg(Arg::with_name("timeout")
+                .short("t")
+                .long("timeout")
+                .value_name("SECONDS")
+                .default_value("3600")
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Maximum time to wait for survey-in to complete"))
+            .arg(Arg::with_name("store")
+                .short("s")
+                .long("store")
+                .help("Stores surveyed position as fixed position in configuration file"))
+            .arg(Arg::with_name("configfile")
+                .short("f")
+                .long("file")
+                .value_name("CONFIGFILE")
+                .help("Path to configuration file")))
+
+        .subcommand(SubCommand::with_name("timemark")
+            .about("Streams EXTINT time mark events as JSON lines (UBX-TIM-TM2)")
+            .arg(Arg::with_name("count")
+                .short("n")
+                .long("count")
+                .value_name("EVENTS")
+                .validator(|v| match v.parse::<u64>() {
+                    Ok(n) if n > 0 => Ok(()),
+                    _ => Err("count must be at least 1".to_string()),
+                })
+                .help("Stops after number of events, runs until stopped otherwise")));
     app
 }
 
@@ -140,7 +434,7 @@ fn check_port(device_name: &str) -> Result<(), String> {
 
     // Ensure device is not in use
     let output = Command::new("fuser")
-        .args(&[device_name])
+        .args([device_name])
         .output()
         .map_err(|e| format!("error executing fuser command ({:?})", e))?;
     if !output.stdout.is_empty() {
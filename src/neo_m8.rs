@@ -3,29 +3,85 @@ use log::debug;
 use std::collections::HashMap;
 use std::{thread, time};
 
-use crate::config_file::{Angles, Xyz};
+use crate::config_file::{
+    Angles, FixedPosition, Navx5, TimeMode, TimePulseConfig, WheelTicks, Xyz,
+};
+use crate::ubxlib::cid::UbxCID;
 use crate::ubxlib::error::Error;
+use crate::ubxlib::frame::{UbxFrame, UbxFrameDeSerialize};
 use crate::ubxlib::server_tty::ServerTty;
+use crate::ubxlib::ubx_ack::{CLS_ACK, ID_NAK};
 use crate::ubxlib::ubx_cfg_cfg::UbxCfgCfgAction;
 use crate::ubxlib::ubx_cfg_esfalg::{UbxCfgEsfAlg, UbxCfgEsfAlgPoll};
-use crate::ubxlib::ubx_cfg_esfla::{LeverArmType, UbxCfgEsflaSet};
+use crate::ubxlib::ubx_cfg_esfla::{LeverArmType, UbxCfgEsfla, UbxCfgEsflaPoll, UbxCfgEsflaSet};
+use crate::ubxlib::ubx_cfg_esfwt::{
+    DataCfgEsfwt, UbxCfgEsfwt, UbxCfgEsfwtPoll, FLAG_DIR_PIN_POL, FLAG_USE_WT_SPEED,
+};
 use crate::ubxlib::ubx_cfg_gnss::{SystemName, UbxCfgGnss, UbxCfgGnssPoll};
+use crate::ubxlib::ubx_cfg_hnr::{UbxCfgHnr, UbxCfgHnrPoll};
+use crate::ubxlib::ubx_cfg_msg::{UbxCfgMsg, UbxCfgMsgPoll};
 use crate::ubxlib::ubx_cfg_nav5::{UbxCfgNav5, UbxCfgNav5Poll};
+use crate::ubxlib::ubx_cfg_navx5::{
+    DataCfgNavx5, UbxCfgNavx5, UbxCfgNavx5Poll, AOP_CFG_USE_AOP, MASK1_ACK_AID, MASK1_AOP,
+    MASK1_INITIAL_3D_FIX, MASK1_MIN_CNO, MASK1_MIN_MAX, MASK1_PPP, MASK1_WKN_ROLL,
+};
 use crate::ubxlib::ubx_cfg_nmea::{UbxCfgNmea, UbxCfgNmeaPoll};
-use crate::ubxlib::ubx_cfg_prt::{UbxCfgPrtPoll, UbxCfgPrtUart};
+use crate::ubxlib::ubx_cfg_prt::{
+    protocols_to_string, PortId, UbxCfgPrt, UbxCfgPrtPoll, BAUDRATES, PROTO_NMEA, PROTO_UBX,
+};
 use crate::ubxlib::ubx_cfg_rate::{UbxCfgRate, UbxCfgRatePoll};
 use crate::ubxlib::ubx_cfg_rst::UbxCfgRstAction;
-use crate::ubxlib::ubx_mga_init_time_utc::UbxMgaIniTimeUtc;
+use crate::ubxlib::ubx_cfg_tmode2::{
+    DataCfgTmode2, TimeModeType, UbxCfgTmode2, UbxCfgTmode2Poll, FLAG_LLA,
+};
+use crate::ubxlib::ubx_cfg_tp5::{
+    DataCfgTp5, TimePulse, UbxCfgTp5, UbxCfgTp5Poll, FLAG_ACTIVE, FLAG_ALIGN_TO_TOW, FLAG_IS_FREQ,
+    FLAG_IS_LENGTH, FLAG_LOCKED_OTHER_SET, FLAG_LOCK_GNSS_FREQ, FLAG_POLARITY,
+};
+use crate::ubxlib::ubx_esf_alg::{DataEsfAlg, UbxEsfAlg, UbxEsfAlgPoll};
+use crate::ubxlib::ubx_esf_meas::{Measurement, UbxEsfMeas};
+use crate::ubxlib::ubx_esf_resetalg::UbxEsfResetAlgAction;
+use crate::ubxlib::ubx_esf_status::{UbxEsfStatus, UbxEsfStatusPoll};
+use crate::ubxlib::ubx_mga_data::UbxMgaData;
+use crate::ubxlib::ubx_mga_dbd::UbxMgaDbdPoll;
+use crate::ubxlib::ubx_mga_init_pos_llh::UbxMgaIniPosLlh;
+use crate::ubxlib::ubx_mga_init_time_utc::{TimeRef, UbxMgaIniTimeUtc};
 use crate::ubxlib::ubx_mon_ver::{UbxMonVer, UbxMonVerPoll};
-use crate::ubxlib::ubx_upd_sos::{Response, UbxUpdSos, UbxUpdSosAction, UbxUpdSosPoll};
+use crate::ubxlib::ubx_nav_att::{DataNavAtt, UbxNavAtt, UbxNavAttPoll};
+use crate::ubxlib::ubx_nav_pvt::{DataNavPvt, UbxNavPvt, UbxNavPvtPoll};
+use crate::ubxlib::ubx_tim_svin::{DataTimSvin, UbxTimSvin, UbxTimSvinPoll};
+use crate::ubxlib::ubx_upd_sos::{Response, SosMessage, UbxUpdSos, UbxUpdSosAction, UbxUpdSosPoll};
+
+// Result of bitrate detection
+#[derive(Debug, Clone, Copy)]
+pub struct PortInfo {
+    pub baudrate: usize,
+    pub out_proto_mask: u16, // protocols seen (passive) or configured (active)
+}
 
 pub struct NeoM8 {
+    #[allow(dead_code)]
     pub device_name: String,
     server: ServerTty,
 }
 
 impl NeoM8 {
-    const BITRATES: [usize; 2] = [115200, 9600];
+    // Bitrates in order of detection, most likely ones first
+    const BITRATES: [usize; 9] = [
+        115200, 9600, 921600, 460800, 230400, 57600, 38400, 19200, 4800,
+    ];
+    // Passive detection: listen time per bitrate, just above one epoch at 1 Hz,
+    // and checksum verified frames required per protocol
+    const SCAN_TIME: u128 = 1100;
+    const SCAN_MIN_FRAMES: usize = 1;
+    // Delay after each assistance message if not acknowledged
+    const MGA_PACING_DELAY: u64 = 20;
+    // Navigation database dump is complete if no further message arrives within this time
+    const DBD_IDLE_TIME: u128 = 1000;
+    // Time to wait for confirmation of SOS backup creation
+    const SOS_BACKUP_TIMEOUT: u128 = 5000;
+    // Time for receiver to answer polls again after a software reset
+    const RESTART_TIMEOUT: time::Duration = time::Duration::from_secs(5);
 
     pub fn new(device: &str) -> Self {
         Self {
@@ -34,33 +90,59 @@ impl NeoM8 {
         }
     }
 
-    /*
-    pub fn detect_baudrate(&mut self) -> Result<usize, Error> {
+    // Tries passive detection first, falls back to active polling if receiver is silent
+    pub fn detect_baudrate(&mut self) -> Result<PortInfo, Error> {
+        match self.detect_baudrate_passive() {
+            Ok(info) => Ok(info),
+            Err(_) => {
+                debug!("passive detection failed, polling receiver");
+                self.detect_baudrate_active()
+            }
+        }
+    }
+
+    pub fn detect_baudrate_passive(&mut self) -> Result<PortInfo, Error> {
         for baud in NeoM8::BITRATES.iter() {
-            debug!("checking {} bps", baud);
+            debug!("listening at {} bps", baud);
 
             self.server.set_baudrate(*baud)?;
 
             // try to receive ubx or NMEA frames at configured bitrate
-            match self.server.scan() {
-                Ok(_) => {
-                    return Ok(*baud);
+            let (ubx, nmea, bytes) = self.server.scan(NeoM8::SCAN_TIME, NeoM8::SCAN_MIN_FRAMES)?;
+
+            // A transmitting receiver produces bytes at any bitrate, only garbled ones
+            // at the wrong one. Nothing received means it is silent, no need to go on.
+            if bytes == 0 {
+                debug!("no data received, receiver silent");
+                return Err(Error::BaudRateDetectionFailed);
+            }
+
+            if ubx >= NeoM8::SCAN_MIN_FRAMES || nmea >= NeoM8::SCAN_MIN_FRAMES {
+                let mut protocols = 0;
+                if ubx > 0 {
+                    protocols |= PROTO_UBX;
                 }
-                Err(_) => {
-                    debug!("bitrate {:?} not working", baud);
+                if nmea > 0 {
+                    protocols |= PROTO_NMEA;
                 }
+
+                return Ok(PortInfo {
+                    baudrate: *baud,
+                    out_proto_mask: protocols,
+                });
             }
-        };
+
+            debug!("bitrate {:?} not working", baud);
+        }
 
         Err(Error::BaudRateDetectionFailed)
     }
-    */
 
-    pub fn detect_baudrate_active(&mut self) -> Result<usize, Error> {
+    pub fn detect_baudrate_active(&mut self) -> Result<PortInfo, Error> {
         let retries = self.server.set_retries(2);
         let delay = self.server.set_retry_delay(250);
 
-        let mut result: Result<usize, Error> = Err(Error::BaudRateDetectionFailed);
+        let mut result: Result<PortInfo, Error> = Err(Error::BaudRateDetectionFailed);
 
         for baud in NeoM8::BITRATES.iter() {
             debug!("checking {} bps", baud);
@@ -68,7 +150,7 @@ impl NeoM8 {
             self.server.set_baudrate(*baud)?;
 
             let poll = UbxCfgPrtPoll::create();
-            let mut response = UbxCfgPrtUart::create();
+            let mut response = UbxCfgPrt::create();
 
             /*
              * Try to query current port settings.
@@ -80,7 +162,10 @@ impl NeoM8 {
                 Ok(_) => {
                     if response.data.baudrate as usize == *baud {
                         debug!("bitrate matches");
-                        result = Ok(*baud);
+                        result = Ok(PortInfo {
+                            baudrate: *baud,
+                            out_proto_mask: response.data.out_proto_mask,
+                        });
                         break;
                     } else {
                         debug!(
@@ -124,13 +209,21 @@ impl NeoM8 {
         Ok(())
     }
 
-    pub fn sos_check(&mut self) -> Result<(), Error> {
+    // Reports result of backup restore at receiver startup
+    pub fn sos_state(&mut self) -> Result<Response, Error> {
         let mut set = UbxUpdSos::create();
         let poll = UbxUpdSosPoll::create();
         self.server.poll(&poll, &mut set)?;
-        debug!("SoS State reported is {:?}", set.data.response);
+        debug!("SoS State reported is {:?}", set.data.message());
 
-        match set.data.response {
+        match set.data.message() {
+            SosMessage::Restore(response) => Ok(response),
+            _ => Ok(Response::Unknown),
+        }
+    }
+
+    pub fn sos_check(&mut self) -> Result<(), Error> {
+        match self.sos_state()? {
             Response::Restored => Ok(()),
             Response::NotRestoredNoBackup => Err(Error::ModemNobackup),
             _ => Err(Error::ModemBackupRestoreFailed),
@@ -146,10 +239,38 @@ impl NeoM8 {
         // request before commanding next messages
         thread::sleep(time::Duration::from_millis(200));
 
+        // Receiver acknowledges the command and reports creation of the backup afterwards.
+        // Both are received together, as the generic acknowledge would filter the report.
         let set = UbxUpdSosAction::backup();
-        self.server.set(&set)?;
+        self.server.fire_and_forget(&set)?;
 
-        Ok(())
+        let nak_cid = UbxCID::new(CLS_ACK, ID_NAK);
+        let sos_cid = set.cid;
+        let mut sos = UbxUpdSos::create();
+        let mut result = Err(Error::ModemNotResponding);
+        self.server.listen(
+            &[sos_cid, nak_cid],
+            Some(NeoM8::SOS_BACKUP_TIMEOUT),
+            |frame| {
+                if frame.cid == nak_cid {
+                    if frame.data[..] != [sos_cid.cls(), sos_cid.id()] {
+                        return true;
+                    }
+                    result = Err(Error::ModemNAK);
+                    return false;
+                }
+
+                sos.from_bin(&frame.data);
+                match sos.data.message() {
+                    SosMessage::BackupCreated => result = Ok(()),
+                    SosMessage::BackupNotCreated => result = Err(Error::ModemBackupFailed),
+                    _ => return true,
+                }
+                false
+            },
+        )?;
+
+        result
     }
 
     pub fn sos_clear(&mut self) -> Result<(), Error> {
@@ -170,6 +291,37 @@ impl NeoM8 {
         Ok(())
     }
 
+    // Discards learned IMU alignment, receiver restarts alignment from scratch
+    pub fn reset_alignment(&mut self) -> Result<(), Error> {
+        let set = UbxEsfResetAlgAction::create();
+        self.server.set(&set)?;
+
+        Ok(())
+    }
+
+    pub fn clear_sensor_calibration(&mut self) -> Result<(), Error> {
+        let set = UbxCfgRstAction::clear_sensor_fusion();
+        self.server.fire_and_forget(&set)?;
+
+        // Reset is not acknowledged, wait until receiver answers polls again
+        self.wait_for_restart()
+    }
+
+    fn wait_for_restart(&mut self) -> Result<(), Error> {
+        let start = time::Instant::now();
+        loop {
+            thread::sleep(time::Duration::from_millis(200));
+
+            let mut ver_result = UbxMonVer::new();
+            let poll = UbxMonVerPoll::create();
+            match self.server.poll(&poll, &mut ver_result) {
+                Ok(()) => return Ok(()),
+                Err(err) if start.elapsed() >= NeoM8::RESTART_TIMEOUT => return Err(err),
+                Err(err) => debug!("receiver not yet restarted: {}", err),
+            }
+        }
+    }
+
     pub fn factory_reset(&mut self) -> Result<(), Error> {
         let set = UbxCfgCfgAction::factory_reset();
         self.server.fire_and_forget(&set)?;
@@ -189,11 +341,11 @@ impl NeoM8 {
     }
 
     pub fn set_modem_baudrate(&mut self, baudrate: u32) -> Result<(), Error> {
-        if baudrate != 115200 && baudrate != 9600 {
+        if !BAUDRATES.contains(&baudrate) {
             return Err(Error::InvalidArgument);
         }
 
-        let mut set = UbxCfgPrtUart::create();
+        let mut set = UbxCfgPrt::create();
         let poll = UbxCfgPrtPoll::create();
         self.server.poll(&poll, &mut set)?;
 
@@ -209,8 +361,71 @@ impl NeoM8 {
         Ok(())
     }
 
+    // Changes baudrate of a port other than the one used for communication (UART1)
+    pub fn set_port_baudrate(&mut self, port: PortId, baudrate: u32) -> Result<(), Error> {
+        if port == PortId::Uart1 || !BAUDRATES.contains(&baudrate) {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut set = UbxCfgPrt::create();
+        let poll = UbxCfgPrtPoll::port(port);
+        self.server.poll(&poll, &mut set)?;
+
+        if set.data.baudrate != baudrate {
+            debug!("setting baudrate of {:?} to {} bps", port, baudrate);
+            set.data.baudrate = baudrate;
+            debug!("new settings {:?}", set.data);
+            self.server.set(&set)?;
+
+            // Read back to make sure receiver accepted the baudrate
+            let mut check = UbxCfgPrt::create();
+            self.server.poll(&poll, &mut check)?;
+            if check.data.baudrate != baudrate {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_port_protocols(
+        &mut self,
+        port: PortId,
+        in_proto_mask: Option<u16>,
+        out_proto_mask: Option<u16>,
+    ) -> Result<(), Error> {
+        let mut set = UbxCfgPrt::create();
+        let poll = UbxCfgPrtPoll::port(port);
+        self.server.poll(&poll, &mut set)?;
+
+        let in_mask = in_proto_mask.unwrap_or(set.data.in_proto_mask);
+        let out_mask = out_proto_mask.unwrap_or(set.data.out_proto_mask);
+
+        if set.data.in_proto_mask != in_mask || set.data.out_proto_mask != out_mask {
+            debug!(
+                "setting protocols of {:?} to in {}, out {}",
+                port,
+                protocols_to_string(in_mask),
+                protocols_to_string(out_mask)
+            );
+            set.data.in_proto_mask = in_mask;
+            set.data.out_proto_mask = out_mask;
+            debug!("new settings {:?}", set.data);
+            self.server.set(&set)?;
+
+            // Read back to make sure receiver accepted the protocol masks
+            let mut check = UbxCfgPrt::create();
+            self.server.poll(&poll, &mut check)?;
+            if check.data.in_proto_mask != in_mask || check.data.out_proto_mask != out_mask {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn set_update_rate(&mut self, rate_in_hz: u16) -> Result<(), Error> {
-        if rate_in_hz < 1 || rate_in_hz > 10 {
+        if !(1..=10).contains(&rate_in_hz) {
             return Err(Error::InvalidArgument);
         }
 
@@ -230,6 +445,65 @@ impl NeoM8 {
         Ok(())
     }
 
+    pub fn set_hnr_rate(&mut self, rate_in_hz: u8) -> Result<(), Error> {
+        if !(1..=30).contains(&rate_in_hz) {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut set = UbxCfgHnr::create();
+        let poll = UbxCfgHnrPoll::create();
+        self.server.poll(&poll, &mut set)?;
+
+        if set.data.high_nav_rate != rate_in_hz {
+            debug!("setting high navigation rate to {} Hz", rate_in_hz);
+            set.data.high_nav_rate = rate_in_hz;
+            self.server.set(&set)?;
+        }
+
+        Ok(())
+    }
+
+    // Passes received frames of given class/ids to callback, see ServerTty::listen
+    pub fn listen<F>(
+        &mut self,
+        cids: &[UbxCID],
+        timeout_in_ms: Option<u128>,
+        callback: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(&UbxFrame) -> bool,
+    {
+        self.server.listen(cids, timeout_in_ms, callback)
+    }
+
+    pub fn message_rate(&mut self, msg: UbxCID, port: PortId) -> Result<u8, Error> {
+        let mut res = UbxCfgMsg::create();
+        let poll = UbxCfgMsgPoll::create(msg);
+        self.server.poll(&poll, &mut res)?;
+
+        Ok(res.data.rates[port as usize])
+    }
+
+    pub fn set_message_rate(&mut self, msg: UbxCID, port: PortId, rate: u8) -> Result<(), Error> {
+        let mut set = UbxCfgMsg::create();
+        let poll = UbxCfgMsgPoll::create(msg);
+        self.server.poll(&poll, &mut set)?;
+
+        if set.data.rates[port as usize] != rate {
+            debug!("setting rate of {:?} on {:?} to {}", msg, port, rate);
+            set.data.rates[port as usize] = rate;
+            debug!("new settings {:?}", set.data);
+            self.server.set(&set)?;
+
+            // Read back to make sure receiver accepted the new rate
+            if self.message_rate(msg, port)? != rate {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn set_nmea_protocol_version(&mut self, version: &str) -> Result<(), Error> {
         let ubx_ver = match version {
             "4.0" => 0x40,
@@ -308,8 +582,24 @@ impl NeoM8 {
         Ok(())
     }
 
+    pub fn imu_angles(&mut self) -> Result<(Angles, bool), Error> {
+        let mut res = UbxCfgEsfAlg::create();
+        let poll = UbxCfgEsfAlgPoll::create();
+        self.server.poll(&poll, &mut res)?;
+
+        let angles = Angles {
+            yaw: res.data.yaw as f32 / 100.0,
+            pitch: res.data.pitch as f32 / 100.0,
+            roll: res.data.roll as f32 / 100.0,
+        };
+        Ok((angles, res.data.auto_alignment()))
+    }
+
     pub fn set_imu_angles(&mut self, angles: Angles) -> Result<(), Error> {
-        if angles.yaw > 360 || angles.pitch.abs() > 90 || angles.roll.abs() > 180 {
+        if !(0.0..=360.0).contains(&angles.yaw)
+            || angles.pitch.abs() > 90.0
+            || angles.roll.abs() > 180.0
+        {
             return Err(Error::InvalidArgument);
         }
 
@@ -317,13 +607,250 @@ impl NeoM8 {
         let poll = UbxCfgEsfAlgPoll::create();
         self.server.poll(&poll, &mut set)?;
 
-        set.data.yaw = angles.yaw as u32 * 100;
-        set.data.pitch = angles.pitch as i16 * 100;
-        set.data.roll = angles.roll as i16 * 100;
+        // Receiver resolution is 0.01°
+        let yaw = (angles.yaw * 100.0).round() as u32;
+        let pitch = (angles.pitch * 100.0).round() as i16;
+        let roll = (angles.roll * 100.0).round() as i16;
+
+        set.data.yaw = yaw;
+        set.data.pitch = pitch;
+        set.data.roll = roll;
         debug!("new IMU settings {:?}", set.data);
 
         self.server.set(&set)?;
 
+        // Verify settings
+        let mut res = UbxCfgEsfAlg::create();
+        self.server.poll(&poll, &mut res)?;
+        if (res.data.yaw, res.data.pitch, res.data.roll) != (yaw, pitch, roll) {
+            debug!("IMU angles readback {:?}", res.data);
+            return Err(Error::ModemReadbackMismatch);
+        }
+
+        Ok(())
+    }
+
+    pub fn wheel_ticks(&mut self) -> Result<DataCfgEsfwt, Error> {
+        let mut res = UbxCfgEsfwt::create();
+        let poll = UbxCfgEsfwtPoll::create();
+        self.server.poll(&poll, &mut res)?;
+
+        Ok(res.data)
+    }
+
+    pub fn set_wheel_ticks(&mut self, config: &WheelTicks) -> Result<(), Error> {
+        let mut set = UbxCfgEsfwt::create();
+        let poll = UbxCfgEsfwtPoll::create();
+        self.server.poll(&poll, &mut set)?;
+
+        let old = set.data.clone();
+        if let Some(factor) = config.factor {
+            set.data.wt_factor = (factor * 1e6).round() as u32;
+        }
+        if let Some(error) = config.quant_error {
+            set.data.wt_quant_error = (error * 1e6).round() as u32;
+        }
+        if let Some(count_max) = config.count_max {
+            set.data.wt_count_max = count_max;
+        }
+        if let Some(latency) = config.latency {
+            set.data.wt_latency = latency;
+        }
+        if let Some(inverted) = config.dir_inverted {
+            set.data.set_flag(FLAG_DIR_PIN_POL, inverted);
+        }
+        if let Some(use_speed) = config.use_speed {
+            set.data.set_flag(FLAG_USE_WT_SPEED, use_speed);
+        }
+
+        if set.data != old {
+            debug!("new wheel tick settings {:?}", set.data);
+            self.server.set(&set)?;
+
+            // Verify settings
+            if self.wheel_ticks()? != set.data {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn time_pulse(&mut self, pulse: TimePulse) -> Result<DataCfgTp5, Error> {
+        let mut res = UbxCfgTp5::create();
+        let poll = UbxCfgTp5Poll::pulse(pulse);
+        self.server.poll(&poll, &mut res)?;
+
+        Ok(res.data)
+    }
+
+    pub fn set_time_pulse(&mut self, config: &TimePulseConfig) -> Result<(), Error> {
+        let mut set = UbxCfgTp5::create();
+        set.data = self.time_pulse(config.pulse)?;
+
+        let old = set.data.clone();
+        if let Some(active) = config.active {
+            set.data.set_flag(FLAG_ACTIVE, active);
+        }
+        // Config sets unit flags only together with locked and unlocked value
+        if let Some(is_freq) = config.is_freq {
+            set.data.set_flag(FLAG_IS_FREQ, is_freq);
+        }
+        if let Some(value) = config.freq_period {
+            set.data.freq_period = value;
+        }
+        if let Some(value) = config.freq_period_lock {
+            set.data.freq_period_lock = value;
+        }
+        if let Some(is_length) = config.is_length {
+            set.data.set_flag(FLAG_IS_LENGTH, is_length);
+        }
+        if let Some(value) = config.pulse_len_ratio {
+            set.data.pulse_len_ratio = value;
+        }
+        if let Some(value) = config.pulse_len_ratio_lock {
+            set.data.pulse_len_ratio_lock = value;
+        }
+        if config.freq_period_lock.is_some() || config.pulse_len_ratio_lock.is_some() {
+            set.data.set_flag(FLAG_LOCKED_OTHER_SET, true);
+        }
+        if let Some(delay) = config.cable_delay {
+            set.data.ant_cable_delay = delay;
+        }
+        if let Some(delay) = config.rf_delay {
+            set.data.rf_group_delay = delay;
+        }
+        if let Some(delay) = config.user_delay {
+            set.data.user_config_delay = delay;
+        }
+        if let Some(rising) = config.rising {
+            set.data.set_flag(FLAG_POLARITY, rising);
+        }
+        if let Some(grid) = config.grid {
+            set.data.set_flag(FLAG_ALIGN_TO_TOW, true);
+            set.data.set_grid(grid);
+        }
+        if let Some(sync) = config.sync_gnss {
+            set.data.set_flag(FLAG_LOCK_GNSS_FREQ, sync);
+        }
+
+        if set.data != old {
+            debug!("new {} settings {:?}", config.pulse.name(), set.data);
+            self.server.set(&set)?;
+
+            // Verify settings
+            if self.time_pulse(config.pulse)? != set.data {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn time_mode(&mut self) -> Result<DataCfgTmode2, Error> {
+        let mut res = UbxCfgTmode2::create();
+        let poll = UbxCfgTmode2Poll::create();
+        self.server.poll(&poll, &mut res)?;
+
+        Ok(res.data)
+    }
+
+    pub fn set_time_mode(&mut self, config: &TimeMode) -> Result<(), Error> {
+        let mut set = UbxCfgTmode2::create();
+        set.data = self.time_mode()?;
+
+        let old = set.data.clone();
+        if let Some(mode) = config.mode {
+            set.data.time_mode = mode as u8;
+        }
+        if let Some(min_dur) = config.svin_min_dur {
+            set.data.svin_min_dur = min_dur;
+        }
+        if let Some(acc_limit) = config.svin_acc_limit {
+            set.data.svin_acc_limit = acc_limit;
+        }
+        match config.fixed_pos {
+            Some(FixedPosition::Ecef { x, y, z }) => {
+                set.data.flags &= !FLAG_LLA;
+                set.data.ecef_x_or_lat = x;
+                set.data.ecef_y_or_lon = y;
+                set.data.ecef_z_or_alt = z;
+            }
+            Some(FixedPosition::Llh { lat, lon, height }) => {
+                set.data.flags |= FLAG_LLA;
+                set.data.ecef_x_or_lat = lat;
+                set.data.ecef_y_or_lon = lon;
+                set.data.ecef_z_or_alt = height;
+            }
+            None => (),
+        }
+        if let Some(acc) = config.fixed_pos_acc {
+            set.data.fixed_pos_acc = acc;
+        }
+
+        if set.data != old {
+            debug!("new time mode settings {:?}", set.data);
+            self.server.set(&set)?;
+
+            // Verify settings
+            if self.time_mode()? != set.data {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    // A running or completed survey-in is restarted by disabling time mode first
+    pub fn start_survey_in(&mut self, min_dur: u32, acc_limit: u32) -> Result<(), Error> {
+        let mut set = UbxCfgTmode2::create();
+        set.data = self.time_mode()?;
+
+        if set.data.time_mode != TimeModeType::Disabled as u8 {
+            set.data.time_mode = TimeModeType::Disabled as u8;
+            self.server.set(&set)?;
+        }
+
+        set.data.time_mode = TimeModeType::SurveyIn as u8;
+        set.data.svin_min_dur = min_dur;
+        set.data.svin_acc_limit = acc_limit;
+        debug!("starting survey-in {:?}", set.data);
+        self.server.set(&set)?;
+
+        if self.time_mode()? != set.data {
+            return Err(Error::ModemReadbackMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn survey_in_status(&mut self) -> Result<DataTimSvin, Error> {
+        let mut res = UbxTimSvin::create();
+        let poll = UbxTimSvinPoll::create();
+        self.server.poll(&poll, &mut res)?;
+
+        Ok(res.data)
+    }
+
+    pub fn lever_arms(&mut self) -> Result<UbxCfgEsfla, Error> {
+        let mut res = UbxCfgEsfla::new();
+        let poll = UbxCfgEsflaPoll::create();
+        self.server.poll(&poll, &mut res)?;
+        debug!("{:?}", res);
+
+        Ok(res)
+    }
+
+    pub fn set_auto_alignment(&mut self, enable: bool) -> Result<(), Error> {
+        let mut set = UbxCfgEsfAlg::create();
+        let poll = UbxCfgEsfAlgPoll::create();
+        self.server.poll(&poll, &mut set)?;
+
+        if set.data.auto_alignment() != enable {
+            debug!("setting automatic IMU alignment to {}", enable);
+            set.data.set_auto_alignment(enable);
+            self.server.set(&set)?;
+        }
+
         Ok(())
     }
 
@@ -336,26 +863,248 @@ impl NeoM8 {
         set.data.version = 0;
         set.data.num_configs = 1;
         set.data.leverarm_type = armtype;
-        set.data.leverarm_x = (distances.x * 100.0) as i16;
-        set.data.leverarm_y = (distances.y * 100.0) as i16;
-        set.data.leverarm_z = (distances.z * 100.0) as i16;
+        set.data.leverarm_x = (distances.x * 100.0).round() as i16;
+        set.data.leverarm_y = (distances.y * 100.0).round() as i16;
+        set.data.leverarm_z = (distances.z * 100.0).round() as i16;
         debug!("new lever arm settings {:?}", set.data);
 
         self.server.set(&set)?;
 
+        // Verify settings
+        let res = self.lever_arms()?;
+        match res.find(armtype) {
+            Some(arm)
+                if (arm.leverarm_x, arm.leverarm_y, arm.leverarm_z)
+                    == (
+                        set.data.leverarm_x,
+                        set.data.leverarm_y,
+                        set.data.leverarm_z,
+                    ) => {}
+            _ => return Err(Error::ModemReadbackMismatch),
+        }
+
         Ok(())
     }
 
-    pub fn set_assistance_time(&mut self) -> Result<(), Error> {
-        let utc: DateTime<Utc> = Utc::now();
-        debug!("Setting GNSS time to {:?}", utc);
+    pub fn esf_status(&mut self) -> Result<UbxEsfStatus, Error> {
+        let mut status = UbxEsfStatus::new();
+        let poll = UbxEsfStatusPoll::create();
+        self.server.poll(&poll, &mut status)?;
+        debug!("{:?}", status);
+
+        Ok(status)
+    }
+
+    pub fn esf_alg(&mut self) -> Result<DataEsfAlg, Error> {
+        let mut alg = UbxEsfAlg::create();
+        let poll = UbxEsfAlgPoll::create();
+        self.server.poll(&poll, &mut alg)?;
+        debug!("{:?}", alg.data);
+
+        Ok(alg.data)
+    }
+
+    pub fn nav_att(&mut self) -> Result<DataNavAtt, Error> {
+        let mut att = UbxNavAtt::create();
+        let poll = UbxNavAttPoll::create();
+        self.server.poll(&poll, &mut att)?;
+        debug!("{:?}", att.data);
+
+        Ok(att.data)
+    }
+
+    pub fn nav_pvt(&mut self) -> Result<DataNavPvt, Error> {
+        let mut pvt = UbxNavPvt::create();
+        let poll = UbxNavPvtPoll::create();
+        self.server.poll(&poll, &mut pvt)?;
+        debug!("{:?}", pvt.data);
+
+        Ok(pvt.data)
+    }
+
+    // Host supplied measurements are not acknowledged by the receiver
+    pub fn send_measurements(&mut self, time_tag: u32, meas: &[Measurement]) -> Result<(), Error> {
+        let mut msg = UbxEsfMeas::new(time_tag);
+        msg.measurements = meas.to_vec();
+        self.server.fire_and_forget(&msg)
+    }
+
+    // MGA messages are only acknowledged if enabled in UBX-CFG-NAVX5
+    pub fn enable_mga_ack(&mut self) -> Result<(), Error> {
+        let mut set = UbxCfgNavx5::create();
+        let poll = UbxCfgNavx5Poll::create();
+        self.server.poll(&poll, &mut set)?;
+
+        if set.data.ack_aiding != 1 {
+            debug!("enabling MGA acknowledge");
+            set.data.mask1 = MASK1_ACK_AID;
+            set.data.mask2 = 0;
+            set.data.ack_aiding = 1;
+            self.server.set(&set)?;
+
+            let mut res = UbxCfgNavx5::create();
+            self.server.poll(&poll, &mut res)?;
+            if res.data.ack_aiding != 1 {
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn navx5(&mut self) -> Result<DataCfgNavx5, Error> {
+        let mut res = UbxCfgNavx5::create();
+        let poll = UbxCfgNavx5Poll::create();
+        self.server.poll(&poll, &mut res)?;
+
+        Ok(res.data)
+    }
+
+    // Only parameter groups with a changed value are selected in mask1
+    pub fn set_navx5(&mut self, config: &Navx5) -> Result<(), Error> {
+        let mut set = UbxCfgNavx5::create();
+        let poll = UbxCfgNavx5Poll::create();
+        self.server.poll(&poll, &mut set)?;
+
+        let old = set.data.clone();
+        let mut mask1 = 0;
+        if let Some(min_svs) = config.min_svs {
+            set.data.min_svs = min_svs;
+        }
+        if let Some(max_svs) = config.max_svs {
+            set.data.max_svs = max_svs;
+        }
+        if (set.data.min_svs, set.data.max_svs) != (old.min_svs, old.max_svs) {
+            mask1 |= MASK1_MIN_MAX;
+        }
+        if let Some(min_cno) = config.min_cno {
+            set.data.min_cno = min_cno;
+            if min_cno != old.min_cno {
+                mask1 |= MASK1_MIN_CNO;
+            }
+        }
+        if let Some(ini_fix_3d) = config.ini_fix_3d {
+            set.data.ini_fix_3d = ini_fix_3d as u8;
+            if set.data.ini_fix_3d != old.ini_fix_3d {
+                mask1 |= MASK1_INITIAL_3D_FIX;
+            }
+        }
+        if let Some(wkn_rollover) = config.wkn_rollover {
+            set.data.wkn_rollover = wkn_rollover;
+            if wkn_rollover != old.wkn_rollover {
+                mask1 |= MASK1_WKN_ROLL;
+            }
+        }
+        if let Some(ppp) = config.ppp {
+            set.data.use_ppp = ppp as u8;
+            if set.data.use_ppp != old.use_ppp {
+                mask1 |= MASK1_PPP;
+            }
+        }
+        if let Some(aop) = config.aop {
+            if aop {
+                set.data.aop_cfg |= AOP_CFG_USE_AOP;
+            } else {
+                set.data.aop_cfg &= !AOP_CFG_USE_AOP;
+            }
+        }
+        if let Some(max_error) = config.aop_orbit_max_error {
+            set.data.aop_orb_max_err = max_error;
+        }
+        if (set.data.aop_cfg, set.data.aop_orb_max_err) != (old.aop_cfg, old.aop_orb_max_err) {
+            mask1 |= MASK1_AOP;
+        }
+
+        if mask1 != 0 {
+            set.data.mask1 = mask1;
+            set.data.mask2 = 0;
+            debug!("new navigation expert settings {:?}", set.data);
+            self.server.set(&set)?;
+
+            // Verify settings, masks are not part of the configuration
+            let mut res = self.navx5()?;
+            res.mask1 = mask1;
+            res.mask2 = 0;
+            if res != set.data {
+                debug!("navigation expert settings readback {:?}", res);
+                return Err(Error::ModemReadbackMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+    Sets receiver time from a host time source
+
+    - clock is sampled right before sending to keep latency low
+    - with an EXTINT reference the time of the next full second is sent,
+      the host must apply a pulse on EXTINT at that second
+    */
+    pub fn set_assistance_time<F>(
+        &mut self,
+        clock: F,
+        accuracy: time::Duration,
+        leap_secs: Option<i8>,
+        time_ref: TimeRef,
+    ) -> Result<(), Error>
+    where
+        F: Fn() -> DateTime<Utc>,
+    {
+        self.enable_mga_ack()?;
+
+        let mut utc = clock();
+        if time_ref != TimeRef::Receipt {
+            utc = utc.with_nanosecond(0).unwrap() + chrono::Duration::seconds(1);
+        }
+        debug!("Setting GNSS time to {:?} ({:?})", utc, time_ref);
 
         let mut set = UbxMgaIniTimeUtc::create();
-        set.set_date_time(&utc);
+        set.set_date_time(&utc, accuracy, leap_secs, time_ref);
+        self.server.set_mga(&set)?;
 
-        self.server.fire_and_forget(&set)?;
-        // MGA messages are not acked by default
-        // Would have to enable with NAVX5 message
+        Ok(())
+    }
+
+    // Dumps navigation database, returns UBX-MGA-DBD frames that can be sent back with send_mga
+    pub fn navigation_database(&mut self) -> Result<Vec<UbxFrame>, Error> {
+        let poll = UbxMgaDbdPoll::create();
+        self.server.poll_sequence(&poll, NeoM8::DBD_IDLE_TIME)
+    }
+
+    /*
+    Sends assistance data, e.g. from AssistNow files
+
+    - with acknowledge, each message is confirmed before the next is sent
+    - without, messages are paced to not overrun the receiver input buffer
+    */
+    pub fn send_mga(&mut self, msg: &UbxMgaData, ack: bool) -> Result<(), Error> {
+        if ack {
+            self.server.set_mga(msg)
+        } else {
+            self.server.fire_and_forget(msg)?;
+            thread::sleep(time::Duration::from_millis(NeoM8::MGA_PACING_DELAY));
+            Ok(())
+        }
+    }
+
+    // Sets approximate receiver position, lat/lon in 1e-7°, height and accuracy in mm
+    pub fn set_assistance_position(
+        &mut self,
+        lat: i32,
+        lon: i32,
+        height: i32,
+        acc: u32,
+    ) -> Result<(), Error> {
+        self.enable_mga_ack()?;
+
+        let mut set = UbxMgaIniPosLlh::create();
+        set.data.lat = lat;
+        set.data.lon = lon;
+        set.data.alt = height / 10;
+        set.data.pos_acc = acc / 10;
+        debug!("Setting GNSS position to {:?}", set.data);
+        self.server.set_mga(&set)?;
 
         Ok(())
     }
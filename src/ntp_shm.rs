@@ -0,0 +1,276 @@
+/*
+NTP shared memory reference clock
+
+Receiver time samples are written to the shared memory segment read by the
+SHM refclock driver of ntpd and chrony, e.g. for chrony
+
+  refclock SHM 0 refid GNSS
+
+The segment key is 0x4e545030 + unit ("NTP0"). Units 0 and 1 are only
+accessible by root, higher units by all users.
+
+Samples use the NAV-TIMEUTC epoch time and the system clock time when the
+message is received. If TIM-TP is enabled, only epochs announced as pulse
+by TIM-TP are written and the receiver time is the exact pulse time. The
+receive time is still taken on arrival of the serial message, which lags
+the epoch by tens to hundreds of ms depending on bitrate and the messages
+output before. The refclock needs an offset to compensate this bias and is
+only accurate to a few ms even then, use a PPS refclock locked to it for
+better accuracy.
+*/
+
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+
+use chrono::prelude::*;
+
+const SHM_KEY_BASE: i32 = 0x4e54_5030;
+
+// Jitter of serial message arrival, about 30 ms
+pub const PRECISION: i32 = -5;
+
+// Segment layout as defined by ntpd refclock_shm.c
+#[repr(C)]
+struct ShmTime {
+    mode: libc::c_int, // 1: count protocol
+    count: libc::c_int,
+    clock_timestamp_sec: libc::time_t,
+    clock_timestamp_usec: libc::c_int,
+    receive_timestamp_sec: libc::time_t,
+    receive_timestamp_usec: libc::c_int,
+    leap: libc::c_int,
+    precision: libc::c_int,
+    nsamples: libc::c_int,
+    valid: libc::c_int,
+    clock_timestamp_nsec: libc::c_uint,
+    receive_timestamp_nsec: libc::c_uint,
+    dummy: [libc::c_int; 8],
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ShmSample {
+    pub clock: DateTime<Utc>,   // receiver time
+    pub receive: DateTime<Utc>, // system clock time
+    pub leap: i32,              // 0: no leap second warning
+    pub precision: i32,         // log2 of seconds
+}
+
+pub struct NtpShm {
+    shm: *mut ShmTime,
+}
+
+impl NtpShm {
+    pub fn open(unit: u8) -> Result<Self, String> {
+        let perm = if unit < 2 { 0o600 } else { 0o666 };
+        let id = unsafe {
+            libc::shmget(
+                SHM_KEY_BASE + unit as i32,
+                mem::size_of::<ShmTime>(),
+                libc::IPC_CREAT | perm,
+            )
+        };
+        if id < 0 {
+            return Err(format!(
+                "can't get shared memory segment for unit {} ({})",
+                unit,
+                io::Error::last_os_error()
+            ));
+        }
+
+        let shm = unsafe { libc::shmat(id, ptr::null(), 0) };
+        if shm as isize == -1 {
+            return Err(format!(
+                "can't attach shared memory segment for unit {} ({})",
+                unit,
+                io::Error::last_os_error()
+            ));
+        }
+
+        let shm = shm as *mut ShmTime;
+        unsafe {
+            ptr::write_volatile(&mut (*shm).mode, 1);
+            ptr::write_volatile(&mut (*shm).nsamples, 3);
+        }
+        Ok(Self { shm })
+    }
+
+    // Count protocol: count is incremented before and after the update
+    pub fn write(&mut self, sample: &ShmSample) {
+        unsafe {
+            let shm = &mut *self.shm;
+            ptr::write_volatile(&mut shm.valid, 0);
+            ptr::write_volatile(&mut shm.count, shm.count.wrapping_add(1));
+            fence(Ordering::SeqCst);
+
+            shm.clock_timestamp_sec = sample.clock.timestamp() as libc::time_t;
+            shm.clock_timestamp_usec = sample.clock.timestamp_subsec_micros() as libc::c_int;
+            shm.clock_timestamp_nsec = sample.clock.timestamp_subsec_nanos();
+            shm.receive_timestamp_sec = sample.receive.timestamp() as libc::time_t;
+            shm.receive_timestamp_usec = sample.receive.timestamp_subsec_micros() as libc::c_int;
+            shm.receive_timestamp_nsec = sample.receive.timestamp_subsec_nanos();
+            shm.leap = sample.leap;
+            shm.precision = sample.precision;
+
+            fence(Ordering::SeqCst);
+            ptr::write_volatile(&mut shm.count, shm.count.wrapping_add(1));
+            ptr::write_volatile(&mut shm.valid, 1);
+        }
+    }
+}
+
+impl Drop for NtpShm {
+    fn drop(&mut self) {
+        unsafe {
+            libc::shmdt(self.shm as *const libc::c_void);
+        }
+    }
+}
+
+/*
+Combines navigation epochs and announced time pulses
+
+TIM-TP announces the next pulse, it may be received before or after the
+NAV-TIMEUTC of the preceding epoch. Therefore the last two announced pulses
+are kept. Epochs not matching a pulse, e.g. at 2 Hz update rate, are skipped.
+*/
+#[derive(Default)]
+pub struct Refclock {
+    pulses: Vec<DateTime<Utc>>,
+}
+
+impl Refclock {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn pulse(&mut self, time: DateTime<Utc>) {
+        self.pulses.push(time);
+        if self.pulses.len() > 2 {
+            self.pulses.remove(0);
+        }
+    }
+
+    pub fn epoch(&mut self, utc: DateTime<Utc>, received: DateTime<Utc>) -> Option<ShmSample> {
+        let clock = if self.pulses.is_empty() {
+            utc
+        } else {
+            // Epoch time is rounded to ms, pulse time is exact. Receive time
+            // still refers to message arrival, not to the pulse.
+            let pulse = self
+                .pulses
+                .iter()
+                .find(|p| (**p - utc).num_milliseconds().abs() <= 1)?;
+            *pulse
+        };
+
+        Some(ShmSample {
+            clock,
+            receive: received,
+            leap: 0,
+            precision: PRECISION,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    // Unit far above the ones used by ntpd/chrony, unique per test process
+    fn test_unit() -> u8 {
+        200 + (std::process::id() % 50) as u8
+    }
+
+    fn remove_segment(unit: u8) {
+        unsafe {
+            let id = libc::shmget(SHM_KEY_BASE + unit as i32, 0, 0);
+            libc::shmctl(id, libc::IPC_RMID, ptr::null_mut());
+        }
+    }
+
+    // Reads last sample like the refclock driver, but keeps it valid
+    fn read(reader: &NtpShm) -> Option<ShmSample> {
+        unsafe {
+            let shm = &*reader.shm;
+            let count = ptr::read_volatile(&shm.count);
+            fence(Ordering::SeqCst);
+            if ptr::read_volatile(&shm.valid) == 0 {
+                return None;
+            }
+
+            let sample = ShmSample {
+                clock: Utc.timestamp(shm.clock_timestamp_sec, shm.clock_timestamp_nsec),
+                receive: Utc.timestamp(shm.receive_timestamp_sec, shm.receive_timestamp_nsec),
+                leap: shm.leap,
+                precision: shm.precision,
+            };
+
+            fence(Ordering::SeqCst);
+            if ptr::read_volatile(&shm.count) != count {
+                return None;
+            }
+            Some(sample)
+        }
+    }
+
+    #[test]
+    fn write_read_back() {
+        let unit = test_unit();
+        let mut shm = NtpShm::open(unit).unwrap();
+        let sample = ShmSample {
+            clock: Utc.ymd(2020, 11, 4).and_hms_nano(13, 57, 24, 0),
+            receive: Utc.ymd(2020, 11, 4).and_hms_nano(13, 57, 24, 87_654_321),
+            leap: 0,
+            precision: PRECISION,
+        };
+        shm.write(&sample);
+
+        // Second attach, as the refclock driver would do
+        let reader = NtpShm::open(unit).unwrap();
+        let res = read(&reader);
+        drop(shm);
+        drop(reader);
+        remove_segment(unit);
+
+        assert_eq!(res, Some(sample));
+    }
+
+    #[test]
+    fn epochs_without_pulses() {
+        let mut dut = Refclock::new();
+        let utc = Utc.ymd(2020, 11, 4).and_hms_milli(13, 57, 24, 500);
+        let received = utc + Duration::milliseconds(80);
+
+        let sample = dut.epoch(utc, received).unwrap();
+        assert_eq!(sample.clock, utc);
+        assert_eq!(sample.receive, received);
+    }
+
+    #[test]
+    fn epochs_with_pulses() {
+        let mut dut = Refclock::new();
+        let pulse = Utc.ymd(2020, 11, 4).and_hms(13, 57, 24);
+        dut.pulse(pulse);
+        dut.pulse(pulse + Duration::seconds(1));
+
+        // Epoch rounded to ms
+        let utc = pulse + Duration::microseconds(400);
+        let received = pulse + Duration::milliseconds(80);
+        let sample = dut.epoch(utc, received).unwrap();
+        assert_eq!(sample.clock, pulse);
+
+        // Half second epoch has no pulse
+        assert!(dut
+            .epoch(pulse + Duration::milliseconds(500), received)
+            .is_none());
+
+        // Oldest pulse is dropped
+        dut.pulse(pulse + Duration::seconds(2));
+        assert!(dut.epoch(pulse, received).is_none());
+    }
+}
@@ -0,0 +1,179 @@
+/*
+Receiver state kept by the host across power cycles
+
+The last known position is stored on shutdown (sos save) and injected on
+startup (sos clear) to improve time to first fix.
+
+File format, one key=value pair per line
+  time=<RFC 3339 UTC time of position>
+  lat=<degrees>
+  lon=<degrees>
+  height=<meters above ellipsoid>
+  acc=<horizontal accuracy in meters>
+*/
+
+use std::fs;
+use std::path::Path;
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+use crate::atomic_file;
+
+// Positions older or less accurate are not injected
+pub const MAX_POSITION_AGE_HOURS: i64 = 24;
+pub const MAX_POSITION_ACC_MM: u32 = 100_000;
+
+#[derive(Debug, PartialEq)]
+pub struct LastPosition {
+    pub time: DateTime<Utc>,
+    pub lat: i32,    // 1e-7°
+    pub lon: i32,    // 1e-7°
+    pub height: i32, // mm above ellipsoid
+    pub acc: u32,    // mm
+}
+
+impl LastPosition {
+    pub fn to_text(&self) -> String {
+        format!(
+            "time={}\nlat={:.7}\nlon={:.7}\nheight={:.3}\nacc={:.3}\n",
+            self.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.lat as f64 * 1e-7,
+            self.lon as f64 * 1e-7,
+            self.height as f64 / 1000.0,
+            self.acc as f64 / 1000.0
+        )
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut time = None;
+        let mut lat = None;
+        let mut lon = None;
+        let mut height = None;
+        let mut acc = None;
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut kv = line.splitn(2, '=');
+            let key = kv.next().unwrap();
+            let value = kv.next().ok_or(format!("missing value for {}", key))?;
+            let invalid = || format!("invalid value {} for {}", value, key);
+            let number = || value.parse::<f64>().map_err(|_| invalid());
+
+            match key {
+                "time" => {
+                    let t = DateTime::parse_from_rfc3339(value).map_err(|_| invalid())?;
+                    time = Some(t.with_timezone(&Utc));
+                }
+                "lat" => lat = Some((number()? * 1e7).round() as i32),
+                "lon" => lon = Some((number()? * 1e7).round() as i32),
+                "height" => height = Some((number()? * 1000.0).round() as i32),
+                "acc" => acc = Some((number()? * 1000.0).round() as u32),
+                _ => return Err(format!("unknown key {}", key)),
+            }
+        }
+
+        let missing = |key: &str| format!("missing key {}", key);
+        Ok(Self {
+            time: time.ok_or_else(|| missing("time"))?,
+            lat: lat.ok_or_else(|| missing("lat"))?,
+            lon: lon.ok_or_else(|| missing("lon"))?,
+            height: height.ok_or_else(|| missing("height"))?,
+            acc: acc.ok_or_else(|| missing("acc"))?,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("can't read state file {} ({})", path.display(), e))?;
+        Self::from_text(&text)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        atomic_file::write(path, self.to_text().as_bytes())
+            .map_err(|e| format!("can't write state file {} ({})", path.display(), e))
+    }
+
+    // Checks whether position is still good enough to be injected
+    pub fn check(&self, now: DateTime<Utc>) -> Result<(), String> {
+        let age = now.signed_duration_since(self.time);
+        if age < Duration::zero() {
+            return Err("position time is in the future, host clock not set".to_string());
+        }
+        if age > Duration::hours(MAX_POSITION_AGE_HOURS) {
+            return Err(format!("position is stale ({} h old)", age.num_hours()));
+        }
+        if self.acc > MAX_POSITION_ACC_MM {
+            return Err(format!(
+                "position accuracy too low ({:.1} m)",
+                self.acc as f64 / 1000.0
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position() -> LastPosition {
+        LastPosition {
+            time: Utc.ymd(2020, 11, 4).and_hms(10, 20, 30),
+            lat: 473_456_789,
+            lon: -85_123_456,
+            height: 480_500,
+            acc: 2_500,
+        }
+    }
+
+    #[test]
+    fn text_roundtrip() {
+        let text = position().to_text();
+        assert_eq!(
+            text,
+            "time=2020-11-04T10:20:30Z\nlat=47.3456789\nlon=-8.5123456\nheight=480.500\nacc=2.500\n"
+        );
+        assert_eq!(LastPosition::from_text(&text), Ok(position()));
+    }
+
+    #[test]
+    fn file_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("gnss-mgr-state-{}", std::process::id()));
+        let path = dir.join("gnss0.pos");
+
+        position().save(&path).unwrap();
+        assert_eq!(LastPosition::load(&path), Ok(position()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_text() {
+        assert!(LastPosition::from_text("lat=47.0\nlon=8.0\nheight=0\nacc=1").is_err());
+        assert!(LastPosition::from_text("time=yesterday").is_err());
+        assert!(LastPosition::from_text("speed=1").is_err());
+    }
+
+    #[test]
+    fn check() {
+        let pos = position();
+        let now = pos.time + Duration::hours(1);
+        assert!(pos.check(now).is_ok());
+
+        // stale
+        assert!(pos.check(pos.time + Duration::hours(25)).is_err());
+        // host clock behind
+        assert!(pos.check(pos.time - Duration::seconds(1)).is_err());
+
+        let inaccurate = LastPosition {
+            acc: 150_000,
+            ..position()
+        };
+        assert!(inaccurate.check(now).is_err());
+    }
+}
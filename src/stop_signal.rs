@@ -0,0 +1,57 @@
+/*
+Stop request by SIGINT or SIGTERM
+
+Subcommands running until they are stopped install the handler and check
+requested() for each received frame, so they can restore receiver settings
+before exiting. The handler is reset after the first signal, a second signal
+terminates the process immediately, e.g. if the receiver stopped sending.
+*/
+
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STOP: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handler(_signal: libc::c_int) {
+    STOP.store(true, Ordering::SeqCst);
+}
+
+pub fn install() -> Result<(), String> {
+    for signal in &[libc::SIGINT, libc::SIGTERM] {
+        let res = unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = handler as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(*signal, &action, ptr::null_mut())
+        };
+        if res < 0 {
+            return Err(format!(
+                "can't install signal handler ({})",
+                io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn requested() -> bool {
+    STOP.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_on_signal() {
+        install().unwrap();
+        assert!(!requested());
+        unsafe {
+            libc::raise(libc::SIGTERM);
+        }
+        assert!(requested());
+    }
+}
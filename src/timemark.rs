@@ -0,0 +1,162 @@
+/*
+Time mark events from UBX-TIM-TM2
+
+Each received message is written as one JSON object per line, e.g.
+
+  {"channel":0,"count":3,"rising":{...},"falling":null,"acc_ns":25,"time_base":"gnss","leap_seconds":18}
+
+Edges are only included if they are new in the message, otherwise null. Each
+edge has the raw week and time of week and its UTC time, which is null if it
+can't be mapped (time invalid, UTC or leap seconds not yet known).
+*/
+
+use chrono::prelude::*;
+
+use crate::ubxlib::ubx_tim_tm2::{
+    DataTimTm2, TimeBase, FLAG_NEW_FALLING_EDGE, FLAG_NEW_RISING_EDGE,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct Edge {
+    pub week: u16,
+    pub tow_ms: u32,
+    pub tow_sub_ns: u32,
+    pub utc: Option<DateTime<Utc>>,
+}
+
+impl Edge {
+    fn to_json(&self) -> String {
+        let utc = match self.utc {
+            Some(utc) => format!("\"{}\"", utc.to_rfc3339_opts(SecondsFormat::Nanos, true)),
+            None => String::from("null"),
+        };
+        format!(
+            "{{\"week\":{},\"tow_ms\":{},\"tow_sub_ns\":{},\"utc\":{}}}",
+            self.week, self.tow_ms, self.tow_sub_ns, utc
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MarkEvent {
+    pub channel: u8,
+    pub count: u16,
+    pub rising: Option<Edge>,
+    pub falling: Option<Edge>,
+    pub acc_ns: u32,
+    pub time_base: Option<TimeBase>,
+    pub leap_secs: Option<i64>,
+}
+
+impl MarkEvent {
+    pub fn from_data(tm: &DataTimTm2, leap_secs: Option<i64>) -> Self {
+        let rising = if tm.flag(FLAG_NEW_RISING_EDGE) {
+            Some(Edge {
+                week: tm.wn_r,
+                tow_ms: tm.tow_ms_r,
+                tow_sub_ns: tm.tow_sub_ms_r,
+                utc: tm.rising_utc(leap_secs),
+            })
+        } else {
+            None
+        };
+        let falling = if tm.flag(FLAG_NEW_FALLING_EDGE) {
+            Some(Edge {
+                week: tm.wn_f,
+                tow_ms: tm.tow_ms_f,
+                tow_sub_ns: tm.tow_sub_ms_f,
+                utc: tm.falling_utc(leap_secs),
+            })
+        } else {
+            None
+        };
+
+        Self {
+            channel: tm.ch,
+            count: tm.count,
+            rising,
+            falling,
+            acc_ns: tm.acc_est,
+            time_base: tm.time_base(),
+            leap_secs,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let edge = |e: &Option<Edge>| match e {
+            Some(e) => e.to_json(),
+            None => String::from("null"),
+        };
+        let time_base = match self.time_base {
+            Some(TimeBase::Receiver) => "\"receiver\"",
+            Some(TimeBase::Gnss) => "\"gnss\"",
+            Some(TimeBase::Utc) => "\"utc\"",
+            None => "null",
+        };
+        let leap = match self.leap_secs {
+            Some(leap) => leap.to_string(),
+            None => String::from("null"),
+        };
+        format!(
+            "{{\"channel\":{},\"count\":{},\"rising\":{},\"falling\":{},\"acc_ns\":{},\"time_base\":{},\"leap_seconds\":{}}}",
+            self.channel,
+            self.count,
+            edge(&self.rising),
+            edge(&self.falling),
+            self.acc_ns,
+            time_base,
+            leap
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::ubx_tim_tm2::{FLAG_TIME_VALID, FLAG_UTC_AVAILABLE};
+
+    fn mark() -> DataTimTm2 {
+        DataTimTm2 {
+            ch: 0,
+            flags: FLAG_NEW_RISING_EDGE | FLAG_TIME_VALID | FLAG_UTC_AVAILABLE | 0x08, // GNSS
+            count: 3,
+            wn_r: 2130,
+            tow_ms_r: 309_462_000,
+            tow_sub_ms_r: 500_000,
+            wn_f: 2130,
+            tow_ms_f: 309_461_100,
+            tow_sub_ms_f: 0,
+            acc_est: 25,
+        }
+    }
+
+    #[test]
+    fn rising_edge() {
+        let dut = MarkEvent::from_data(&mark(), Some(18));
+        assert!(dut.falling.is_none());
+        assert_eq!(
+            dut.to_json(),
+            "{\"channel\":0,\"count\":3,\
+             \"rising\":{\"week\":2130,\"tow_ms\":309462000,\"tow_sub_ns\":500000,\
+             \"utc\":\"2020-11-04T13:57:24.000500000Z\"},\
+             \"falling\":null,\"acc_ns\":25,\"time_base\":\"gnss\",\"leap_seconds\":18}"
+        );
+    }
+
+    #[test]
+    fn leap_seconds_unknown() {
+        let mut tm = mark();
+        tm.flags |= FLAG_NEW_FALLING_EDGE;
+        let dut = MarkEvent::from_data(&tm, None);
+        assert!(dut.rising.unwrap().utc.is_none());
+        assert_eq!(
+            dut.falling,
+            Some(Edge {
+                week: 2130,
+                tow_ms: 309_461_100,
+                tow_sub_ns: 0,
+                utc: None
+            })
+        );
+    }
+}
@@ -12,7 +12,10 @@ pub enum Error {
     ModemUnexpectedAckNak,
     ModemNobackup,
     ModemBackupRestoreFailed,
+    ModemBackupFailed,
+    ModemReadbackMismatch,
     InvalidArgument,
+    InvalidFrame(usize),
 }
 
 impl fmt::Display for Error {
@@ -27,7 +30,10 @@ impl fmt::Display for Error {
             Error::ModemUnexpectedAckNak => f.write_str("unexpected ACK/NAK received"),
             Error::ModemNobackup => f.write_str("no backup present"),
             Error::ModemBackupRestoreFailed => f.write_str("restoring backup failed"),
+            Error::ModemBackupFailed => f.write_str("creating backup failed"),
+            Error::ModemReadbackMismatch => f.write_str("modem readback does not match setting"),
             Error::InvalidArgument => f.write_str("invalid argument"),
+            Error::InvalidFrame(ofs) => write!(f, "invalid UBX frame at offset {}", ofs),
         }
     }
 }
@@ -44,7 +50,10 @@ impl StdError for Error {
             Error::ModemUnexpectedAckNak => "unexpected ACK/NAK received",
             Error::ModemNobackup => "no backup present",
             Error::ModemBackupRestoreFailed => "restoring backup failed",
+            Error::ModemBackupFailed => "creating backup failed",
+            Error::ModemReadbackMismatch => "modem readback does not match setting",
             Error::InvalidArgument => "invalid argument",
+            Error::InvalidFrame(_) => "invalid UBX frame",
         }
     }
 }
@@ -15,6 +15,7 @@ pub trait UbxFrameSerialize {
 }
 
 pub trait UbxFrameDeSerialize {
+    #[allow(clippy::wrong_self_convention)]
     fn from_bin(&mut self, data: &[u8]);
 }
 
@@ -69,7 +70,7 @@ where
     T: DeserializeOwned,
 {
     fn from_bin(&mut self, data: &[u8]) {
-        self.data = bincode::deserialize(&data).unwrap();
+        self.data = bincode::deserialize(data).unwrap();
     }
 }
 
@@ -178,14 +179,14 @@ mod tests {
 
     #[test]
     fn ack_frame() {
-        let msg = UbxFrame::bytes(UbxCID::new(0x05, 0x01), &[1, 2].to_vec());
+        let msg = UbxFrame::bytes(UbxCID::new(0x05, 0x01), [1, 2].as_ref());
         assert_eq!(msg, [0xb5, 0x62, 0x05, 0x01, 0x02, 0x00, 1, 2, 11, 47]);
     }
 
     #[test]
     fn poll_mon_ver() {
         // Poll UBX-MON-VER: B5 62 0A 04 00 00 0E 34
-        let msg = UbxFrame::bytes(UbxCID::new(0x0A, 0x04), &[].to_vec());
+        let msg = UbxFrame::bytes(UbxCID::new(0x0A, 0x04), [].as_ref());
         assert_eq!(msg, [0xb5, 0x62, 0x0a, 0x04, 0x00, 0x00, 0x0e, 0x34]);
     }
 }
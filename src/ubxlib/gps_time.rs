@@ -0,0 +1,119 @@
+/*
+ * GPS week and time of week conversion
+ */
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+const SECONDS_PER_WEEK: i64 = 7 * 24 * 3600;
+// TAI is ahead of GPS time by a constant offset
+const TAI_GPS_OFFSET: i64 = 19;
+// NTP era 0 starts 1900-01-01
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+pub fn gps_epoch() -> DateTime<Utc> {
+    Utc.ymd(1980, 1, 6).and_hms(0, 0, 0)
+}
+
+// Week and time of week as date and time in the same time scale, no leap seconds applied
+pub fn from_week_tow(week: u16, tow_ms: u32, sub_ms_ns: u32) -> DateTime<Utc> {
+    gps_epoch()
+        + Duration::weeks(week as i64)
+        + Duration::milliseconds(tow_ms as i64)
+        + Duration::nanoseconds(sub_ms_ns as i64)
+}
+
+// GPS to UTC offset from GPS time of week and UTC time of the same navigation epoch
+pub fn leap_seconds(itow_ms: u32, utc: DateTime<Utc>) -> i64 {
+    let gps_tow = ((itow_ms as i64) + 500) / 1000;
+    let utc_secs = (utc - gps_epoch()).num_milliseconds();
+    let utc_tow = ((utc_secs + 500) / 1000).rem_euclid(SECONDS_PER_WEEK);
+    (gps_tow - utc_tow).rem_euclid(SECONDS_PER_WEEK)
+}
+
+/*
+GPS to UTC offset at the given time from an IERS leap-seconds.list file
+
+Entries are NTP time and TAI to UTC offset, the `#@` line holds the expiry
+date of the list. None if the list is expired or has no entry for the time.
+*/
+pub fn leap_seconds_from_list(list: &str, at: DateTime<Utc>) -> Option<i64> {
+    let at_ntp = at.timestamp() + NTP_UNIX_OFFSET;
+    let mut expiry = None;
+    let mut tai_utc = None;
+
+    for line in list.lines() {
+        if let Some(value) = line.strip_prefix("#@") {
+            expiry = value.trim().parse::<i64>().ok();
+            continue;
+        }
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        if let (Some(ntp), Some(offset)) = (fields.next(), fields.next()) {
+            let ntp = ntp.parse::<i64>().ok()?;
+            let offset = offset.parse::<i64>().ok()?;
+            if ntp <= at_ntp {
+                tai_utc = Some(offset);
+            }
+        }
+    }
+
+    if at_ntp >= expiry? {
+        return None;
+    }
+    Some(tai_utc? - TAI_GPS_OFFSET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn week_tow() {
+        let time = from_week_tow(2130, 309_462_000, 500_000);
+        assert_eq!(time, Utc.ymd(2020, 11, 4).and_hms_micro(13, 57, 42, 500));
+    }
+
+    #[test]
+    fn leap() {
+        // 2020-11-04 13:57:24 UTC is 13:57:42 GPS time
+        let utc = Utc.ymd(2020, 11, 4).and_hms_milli(13, 57, 24, 0);
+        assert_eq!(leap_seconds(309_462_000, utc), 18);
+
+        // UTC still in previous GPS week
+        let utc = Utc.ymd(2020, 11, 7).and_hms_milli(23, 59, 50, 0);
+        assert_eq!(leap_seconds(8_000, utc), 18);
+    }
+
+    const LIST: &str = "\
+#	Updated through IERS Bulletin C
+#@	3991593600
+#
+2272060800	10	# 1 Jan 1972
+3644697600	36	# 1 Jul 2015
+3692217600	37	# 1 Jan 2017
+";
+
+    #[test]
+    fn leap_list() {
+        let utc = Utc.ymd(2020, 11, 4).and_hms(13, 57, 24);
+        assert_eq!(leap_seconds_from_list(LIST, utc), Some(18));
+
+        let utc = Utc.ymd(2016, 6, 1).and_hms(0, 0, 0);
+        assert_eq!(leap_seconds_from_list(LIST, utc), Some(17));
+
+        // Before first entry and after expiry on 2026-06-28
+        let utc = Utc.ymd(1971, 1, 1).and_hms(0, 0, 0);
+        assert_eq!(leap_seconds_from_list(LIST, utc), None);
+        let utc = Utc.ymd(2026, 6, 28).and_hms(0, 0, 0);
+        assert_eq!(leap_seconds_from_list(LIST, utc), None);
+    }
+
+    #[test]
+    fn leap_list_invalid() {
+        let utc = Utc.ymd(2020, 11, 4).and_hms(13, 57, 24);
+        // No expiry date
+        assert_eq!(leap_seconds_from_list("3692217600	37\n", utc), None);
+        assert_eq!(leap_seconds_from_list("#@	3991593600\nfoo 37\n", utc), None);
+    }
+}
@@ -2,19 +2,44 @@ pub mod checksum;
 pub mod cid;
 pub mod error;
 pub mod frame;
-// pub mod parser_nmea;
+pub mod gps_time;
+pub mod parser_nmea;
 pub mod parser_ubx;
 pub mod server_tty;
 pub mod ubx_ack;
 pub mod ubx_cfg_cfg;
 pub mod ubx_cfg_esfalg;
 pub mod ubx_cfg_esfla;
+pub mod ubx_cfg_esfwt;
 pub mod ubx_cfg_gnss;
+pub mod ubx_cfg_hnr;
+pub mod ubx_cfg_msg;
 pub mod ubx_cfg_nav5;
+pub mod ubx_cfg_navx5;
 pub mod ubx_cfg_nmea;
 pub mod ubx_cfg_prt;
 pub mod ubx_cfg_rate;
 pub mod ubx_cfg_rst;
+pub mod ubx_cfg_tmode2;
+pub mod ubx_cfg_tp5;
+pub mod ubx_esf_alg;
+pub mod ubx_esf_ins;
+pub mod ubx_esf_meas;
+pub mod ubx_esf_raw;
+pub mod ubx_esf_resetalg;
+pub mod ubx_esf_status;
+pub mod ubx_hnr_att;
+pub mod ubx_hnr_pvt;
+pub mod ubx_mga_ack;
+pub mod ubx_mga_data;
+pub mod ubx_mga_dbd;
+pub mod ubx_mga_init_pos_llh;
 pub mod ubx_mga_init_time_utc;
 pub mod ubx_mon_ver;
+pub mod ubx_nav_att;
+pub mod ubx_nav_pvt;
+pub mod ubx_nav_timeutc;
+pub mod ubx_tim_svin;
+pub mod ubx_tim_tm2;
+pub mod ubx_tim_tp;
 pub mod ubx_upd_sos;
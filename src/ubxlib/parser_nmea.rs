@@ -2,6 +2,9 @@
  * Parser that tries to read NMEA drames from arbitrary byte stream
  *
  * $GNRMC,155215.00,A,4719.13883,N,00758.44996,E,0.259,,171020,2.47,E,A*3E\r\n
+ *
+ * A frame only counts if the checksum consists of two hex digits, matches and
+ * is followed by CR LF. Garbage read at a wrong bitrate must not pass as frame.
  */
 
 use log::debug;
@@ -20,6 +23,7 @@ enum State {
     Data,
     ChkSum1,
     ChkSum2,
+    CarriageReturn,
     LineEnd,
 }
 
@@ -57,6 +61,7 @@ impl ParserNmea {
                 State::Data => self.state_data(data),
                 State::ChkSum1 => self.state_chksum1(data),
                 State::ChkSum2 => self.state_chksum2(data),
+                State::CarriageReturn => self.state_carriage_return(data),
                 State::LineEnd => self.state_lineend(data),
             }
         }
@@ -79,31 +84,49 @@ impl ParserNmea {
     }
 
     fn state_chksum1(&mut self, data: u8) {
-        self.checksum = Self::_to_bin(data) << 4;
-        self.state = State::ChkSum2;
+        match Self::_to_bin(data) {
+            Some(value) => {
+                self.checksum = value << 4;
+                self.state = State::ChkSum2;
+            }
+            None => self.state = State::WaitSync,
+        }
     }
 
     fn state_chksum2(&mut self, data: u8) {
-        self.checksum |= Self::_to_bin(data);
+        match Self::_to_bin(data) {
+            Some(value) => self.checksum |= value,
+            None => {
+                self.state = State::WaitSync;
+                return;
+            }
+        }
 
         if self.checksum == self.checksum_data {
-            self.frames_rx += 1;
-
-            let nmea = std::str::from_utf8(&self.msg_data);
-            if nmea.is_ok() {
-                debug!("{:?}", nmea.unwrap());
-            }
+            self.state = State::CarriageReturn;
         } else {
             debug!("Checksum error {} - {}", self.checksum, self.checksum_data);
+            self.state = State::WaitSync;
         }
+    }
 
-        self.state = State::LineEnd;
+    fn state_carriage_return(&mut self, data: u8) {
+        if data as char == '\r' {
+            self.state = State::LineEnd;
+        } else {
+            self.state = State::WaitSync;
+        }
     }
 
     fn state_lineend(&mut self, data: u8) {
         if data as char == '\n' {
-            self.state = State::WaitSync;
+            self.frames_rx += 1;
+
+            if let Ok(nmea) = std::str::from_utf8(&self.msg_data) {
+                debug!("{:?}", nmea);
+            }
         }
+        self.state = State::WaitSync;
     }
 
     fn _reset(&mut self) {
@@ -112,12 +135,12 @@ impl ParserNmea {
         self.checksum_data = 0;
     }
 
-    fn _to_bin(data: u8) -> u8 {
+    fn _to_bin(data: u8) -> Option<u8> {
         match data as char {
-            '0'..='9' => data - b'0',
-            'a'..='f' => data - b'a' + 10,
-            'A'..='F' => data - b'A' + 10,
-            _ => 0,
+            '0'..='9' => Some(data - b'0'),
+            'a'..='f' => Some(data - b'a' + 10),
+            'A'..='F' => Some(data - b'A' + 10),
+            _ => None,
         }
     }
 }
@@ -128,43 +151,63 @@ mod tests {
 
     #[test]
     fn to_bin() {
-        assert_eq!(ParserNmea::_to_bin('0' as u8), 0);
-        assert_eq!(ParserNmea::_to_bin('9' as u8), 9);
-        assert_eq!(ParserNmea::_to_bin('a' as u8), 10);
-        assert_eq!(ParserNmea::_to_bin('A' as u8), 10);
-        assert_eq!(ParserNmea::_to_bin('f' as u8), 15);
-        assert_eq!(ParserNmea::_to_bin('F' as u8), 15);
-
-        assert_eq!(ParserNmea::_to_bin('x' as u8), 0);
+        assert_eq!(ParserNmea::_to_bin(b'0'), Some(0));
+        assert_eq!(ParserNmea::_to_bin(b'9'), Some(9));
+        assert_eq!(ParserNmea::_to_bin(b'a'), Some(10));
+        assert_eq!(ParserNmea::_to_bin(b'A'), Some(10));
+        assert_eq!(ParserNmea::_to_bin(b'f'), Some(15));
+        assert_eq!(ParserNmea::_to_bin(b'F'), Some(15));
+
+        assert_eq!(ParserNmea::_to_bin(b'x'), None);
     }
 
     #[test]
     fn ok() {
-        let data = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+        let data = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n";
         let mut uut = ParserNmea::new();
         assert_eq!(uut.frames_received(), 0);
-        uut.process(&data.as_bytes());
+        uut.process(data.as_bytes());
         assert_eq!(uut.frames_received(), 1);
     }
 
     #[test]
     fn wrong_checksum() {
-        let data = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6B";
+        let data = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6B\r\n";
         let mut uut = ParserNmea::new();
-        uut.process(&data.as_bytes());
+        uut.process(data.as_bytes());
         assert_eq!(uut.frames_received(), 0);
     }
 
     #[test]
     fn checksum_missing() {
-        let data_fail = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W";
-        let data_ok = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+        let data_fail = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W\r\n";
+        let data_ok = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n";
         let mut uut = ParserNmea::new();
-        uut.process(&data_fail.as_bytes());
+        uut.process(data_fail.as_bytes());
         assert_eq!(uut.frames_received(), 0);
 
         // now next line must be properly read
-        uut.process(&data_ok.as_bytes());
+        uut.process(data_ok.as_bytes());
+        assert_eq!(uut.frames_received(), 1);
+    }
+
+    #[test]
+    fn line_end_missing() {
+        let data = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+        let mut uut = ParserNmea::new();
+        uut.process(data.as_bytes());
+        assert_eq!(uut.frames_received(), 0);
+        uut.process(b"x\n");
+        assert_eq!(uut.frames_received(), 0);
+    }
+
+    #[test]
+    fn checksum_not_hex() {
+        // XOR of "A" is 0x41, 'x' must not be taken as 0
+        let mut uut = ParserNmea::new();
+        uut.process(b"$A*4x\r\n");
+        assert_eq!(uut.frames_received(), 0);
+        uut.process(b"$A*41\r\n");
         assert_eq!(uut.frames_received(), 1);
     }
 }
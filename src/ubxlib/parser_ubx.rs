@@ -33,7 +33,8 @@ pub struct ParserUbx {
     ofs: usize,
 }
 
-const MAX_MESSAGE_LENGTH: usize = 1000;
+// Navigation database dumps (UBX-MGA-DBD) contain long messages
+const MAX_MESSAGE_LENGTH: usize = 8192;
 
 #[derive(Debug)]
 enum State {
@@ -74,11 +75,9 @@ impl ParserUbx {
         self.state = State::Init;
     }
 
-    /*
     pub fn frames_received(&self) -> usize {
         self.frames_rx
     }
-    */
 
     pub fn set_filter(&mut self, cid: UbxCID) {
         self.set_filters(&[cid]);
@@ -385,7 +384,7 @@ mod tests {
     #[test]
     fn invalid_length() {
         let frame: [u8; 32] = [
-            0xB5, 0x62, 0x13, 0x40, 0xe9, 0x03, 0x10, 0x00, 0x00, 0x12, 0xE4, 0x07, 0x09, 0x05,
+            0xB5, 0x62, 0x13, 0x40, 0x01, 0x20, 0x10, 0x00, 0x00, 0x12, 0xE4, 0x07, 0x09, 0x05,
             0x06, 0x28, 0x30, 0x00, 0x40, 0x28, 0xEF, 0x0C, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x51, 0xAC,
         ];
@@ -396,6 +395,37 @@ mod tests {
         assert!(res.is_none());
     }
 
+    #[test]
+    fn long_frames() {
+        // Several frames in one chunk, as received for UBX-MGA-DBD polls
+        let payload: Vec<u8> = (0..2000).map(|i| i as u8).collect();
+        let mut data = UbxFrame::bytes(UbxCID::new(0x13, 0x80), &payload);
+        data.extend(UbxFrame::bytes(UbxCID::new(0x13, 0x80), &payload[0..12]));
+
+        let mut uut = ParserUbx::new();
+        uut.set_filter(UbxCID::new(0x13, 0x80));
+        uut.process(&data);
+
+        let packet = uut.packet().unwrap();
+        assert_eq!(packet.data, payload);
+        let packet = uut.packet().unwrap();
+        assert_eq!(packet.data.len(), 12);
+        assert!(uut.packet().is_none());
+    }
+
+    #[test]
+    fn frames_received() {
+        let mut uut = ParserUbx::new();
+        assert_eq!(uut.frames_received(), 0);
+
+        // Frames are counted even if they don't pass the filter
+        uut.set_filters(&[]);
+        uut.process(&FRAME_1);
+        uut.process(&FRAME_1);
+        assert_eq!(uut.frames_received(), 2);
+        assert!(uut.packet().is_none());
+    }
+
     #[test]
     fn filters() {
         let mut uut = ParserUbx::new();
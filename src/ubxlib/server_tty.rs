@@ -9,10 +9,11 @@ use crate::ubxlib::cid::UbxCID;
 use crate::ubxlib::error::Error;
 use crate::ubxlib::frame::UbxFrame;
 use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameInfo, UbxFrameSerialize};
-// use crate::ubxlib::parser_nmea::ParserNmea;
+use crate::ubxlib::parser_nmea::ParserNmea;
 use crate::ubxlib::parser_ubx::ParserUbx;
 use crate::ubxlib::ubx_ack::UbxAck;
 use crate::ubxlib::ubx_ack::{CLS_ACK, ID_ACK, ID_NAK};
+use crate::ubxlib::ubx_mga_ack::UbxMgaAck;
 
 pub struct ServerTty {
     device_name: String,
@@ -80,43 +81,109 @@ impl ServerTty {
     }
 
     /*
-    pub fn scan(&mut self) -> Result<(), Error> {
-        let port = self.serial_port.as_mut().unwrap();
+    Passively scan received data for UBX and NMEA frames
+
+    - nothing is sent to the receiver
+    - listens for the given time or until both protocols have been
+      seen at least min_frames times
+    - returns number of valid UBX and NMEA frames received
+    */
+    pub fn scan(
+        &mut self,
+        duration_in_ms: u128,
+        min_frames: usize,
+    ) -> Result<(usize, usize, usize), Error> {
+        let mut read_buffer = [0u8; 1024];
+        let port = self.serial_port.as_mut().ok_or(Error::SerialPortNotFound)?;
         let mut nmea_parser = ParserNmea::new();
 
-        let start = Instant::now();
-        let mut elapsed = start.elapsed();
+        // Frames are only counted, none is queued
+        self.parser.set_filters(&[]);
+        self.parser.restart();
         let ubx_frames = self.parser.frames_received();
-        let nmea_frames = nmea_parser.frames_received();
 
-        self.parser.restart();
-        while elapsed.as_millis() < 2000 {
-            let mut read_buffer = [0u8; 1024];
-            let res = port.read(&mut read_buffer[..]);
-            if let Ok(bytes_read) = res {
-                let data = read_buffer[0..bytes_read].to_vec();
-                // debug!("{:?}", data);
-                self.parser.process(&data);
-                nmea_parser.process(&data);
+        let start = Instant::now();
+        let mut ubx = 0;
+        let mut nmea = 0;
+        let mut bytes = 0;
+        while start.elapsed().as_millis() < duration_in_ms {
+            if let Ok(bytes_read) = port.read(&mut read_buffer[..]) {
+                bytes += bytes_read;
+                self.parser.process(&read_buffer[0..bytes_read]);
+                nmea_parser.process(&read_buffer[0..bytes_read]);
+            }
+
+            ubx = self.parser.frames_received() - ubx_frames;
+            nmea = nmea_parser.frames_received();
+            if ubx >= min_frames && nmea >= min_frames {
+                break;
             }
+        }
+
+        // Drop checksum error indications
+        self.parser.empty_queue();
+
+        debug!(
+            "scan: {} bytes, {} ubx frames, {} nmea frames",
+            bytes, ubx, nmea
+        );
+        Ok((ubx, nmea, bytes))
+    }
+
+    /*
+    Listen to periodic receiver messages
+
+    - nothing is sent to the receiver
+    - callback is invoked for every frame with one of the given class/ids
+    - stops when callback returns false or after timeout (if given)
+    - returns number of frames passed to callback
+    */
+    pub fn listen<F>(
+        &mut self,
+        cids: &[UbxCID],
+        timeout_in_ms: Option<u128>,
+        mut callback: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(&UbxFrame) -> bool,
+    {
+        let mut read_buffer = [0u8; 1024];
+        let port = self.serial_port.as_mut().ok_or(Error::SerialPortNotFound)?;
+
+        self.parser.set_filters(cids);
+        self.parser.restart();
+        self.parser.empty_queue();
 
-            let _res = self.parser.packet();
-            if self.parser.frames_received() - ubx_frames > 2 {
-                debug!("ubx frames received");
-                return Ok(());
+        let start = Instant::now();
+        let mut frames = 0;
+        loop {
+            if let Some(timeout) = timeout_in_ms {
+                if start.elapsed().as_millis() >= timeout {
+                    break;
+                }
             }
 
-            if nmea_parser.frames_received() - nmea_frames > 2 {
-                debug!("nmea frames received");
-                return Ok(());
+            if let Ok(bytes_read) = port.read(&mut read_buffer[..]) {
+                self.parser.process(&read_buffer[0..bytes_read]);
             }
 
-            elapsed = start.elapsed();
+            while let Some(p) = self.parser.packet() {
+                if p.cid == self.crc_error_cid {
+                    warn!("checksum error in frame, discarding");
+                    continue;
+                }
+
+                frames += 1;
+                if !callback(&p) {
+                    self.parser.empty_queue();
+                    return Ok(frames);
+                }
+            }
         }
 
-        Err(Error::ModemNotResponding)
+        debug!("listen: timeout, {} frames received", frames);
+        Ok(frames)
     }
-    */
 
     /*
     Poll a receiver status
@@ -160,6 +227,70 @@ impl ServerTty {
         Err(Error::ModemNotResponding)
     }
 
+    /*
+    Poll a sequence of frames, e.g. the navigation database
+
+    - sends the poll message
+    - collects all frames with same class/id as poll message
+    - sequence ends when no further frame is received within idle time
+    - retries in case no answer or a corrupted frame is received
+    */
+    pub fn poll_sequence<TPoll: UbxFrameInfo + UbxFrameSerialize>(
+        &mut self,
+        frame_poll: &TPoll,
+        idle_in_ms: u128,
+    ) -> Result<Vec<UbxFrame>, Error> {
+        debug!("polling {}", frame_poll.name());
+
+        let mut read_buffer = [0u8; 1024];
+        self.parser.set_filter(frame_poll.cid());
+        let data = frame_poll.to_bin();
+
+        for retry in 0..self.max_retries {
+            self.parser.restart();
+            self.parser.empty_queue();
+            self.send(&data)?;
+
+            let mut frames = Vec::new();
+            let mut complete = true;
+            let mut last = Instant::now();
+            loop {
+                // First frame may take as long as a single poll response
+                let limit = if frames.is_empty() {
+                    self.retry_delay_in_ms
+                } else {
+                    idle_in_ms
+                };
+                if !complete || last.elapsed().as_millis() >= limit {
+                    break;
+                }
+
+                let port = self.serial_port.as_mut().ok_or(Error::SerialPortNotFound)?;
+                if let Ok(bytes_read) = port.read(&mut read_buffer[..]) {
+                    self.parser.process(&read_buffer[0..bytes_read]);
+                }
+
+                while let Some(p) = self.parser.packet() {
+                    if p.cid == self.crc_error_cid {
+                        warn!("checksum error in frame, sequence incomplete");
+                        complete = false;
+                        break;
+                    }
+                    frames.push(p);
+                    last = Instant::now();
+                }
+            }
+
+            if complete && !frames.is_empty() {
+                debug!("{} frames received", frames.len());
+                return Ok(frames);
+            }
+            warn!("poll_sequence: no complete answer, retrying {}", retry + 1);
+        }
+
+        Err(Error::ModemNotResponding)
+    }
+
     /*
     Send a set message to modem and wait for acknowledge
 
@@ -200,6 +331,49 @@ impl ServerTty {
         Err(Error::ModemNotResponding)
     }
 
+    /*
+    Send an assistance (MGA) message and wait for UBX-MGA-ACK
+
+    - MGA messages are not acknowledged with ACK/NAK
+    - acknowledge must be enabled with ackAiding in UBX-CFG-NAVX5
+    */
+    pub fn set_mga<TSet: UbxFrameSerialize + UbxFrameInfo>(
+        &mut self,
+        frame_set: &TSet,
+    ) -> Result<(), Error> {
+        debug!("setting {}", frame_set.name());
+
+        let mut ack = UbxMgaAck::create();
+        self.parser.set_filter(ack.cid);
+
+        // Acknowledge contains id and first payload bytes of message
+        let data = frame_set.to_bin();
+        let payload_start = &data[6..(data.len() - 2).min(10)];
+
+        for retry in 0..self.max_retries {
+            self.parser.empty_queue();
+            self.send(&data)?;
+
+            // Acknowledges for other messages are skipped, e.g. late ones for previous messages
+            while let Ok(packet) = self.wait() {
+                ack.from_bin(&packet.data);
+                if ack.data.msg_id != frame_set.cid().id()
+                    || !ack.data.msg_payload_start.starts_with(payload_start)
+                {
+                    continue;
+                }
+                if ack.data.accepted() {
+                    return Ok(());
+                }
+                warn!("{} rejected: {}", frame_set.name(), ack.data.info_name());
+                return Err(Error::ModemNAK);
+            }
+            warn!("set_mga: timeout, retrying {}", retry + 1);
+        }
+
+        Err(Error::ModemNotResponding)
+    }
+
     /*
     Send a set message to modem without waiting for a response
     (fire and forget)
@@ -225,7 +399,7 @@ impl ServerTty {
     fn send(&mut self, data: &[u8]) -> Result<(), Error> {
         // debug!("{} bytes to send {:?}", data.len(), data);
         let port = self.serial_port.as_mut().unwrap();
-        let res = port.write(&data);
+        let res = port.write(data);
         match res {
             Ok(bytes_written) => {
                 if bytes_written == data.len() {
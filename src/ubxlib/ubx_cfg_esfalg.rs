@@ -22,6 +22,21 @@ pub struct DataCfgEsfAlg {
     pub roll: i16,     // 1e-2, -180..180°
 }
 
+impl DataCfgEsfAlg {
+    // Bit 8 enables automatic IMU mount alignment
+    pub fn auto_alignment(&self) -> bool {
+        self.bitfield & 0x100 != 0
+    }
+
+    pub fn set_auto_alignment(&mut self, enable: bool) {
+        if enable {
+            self.bitfield |= 0x100;
+        } else {
+            self.bitfield &= !0x100;
+        }
+    }
+}
+
 pub struct UbxCfgEsfAlg {}
 
 impl UbxCfgEsfAlg {
@@ -65,13 +80,27 @@ mod tests {
         assert_eq!(dut.data.roll, 0x0506i16);
     }
 
+    #[test]
+    fn auto_alignment() {
+        let mut dut = UbxCfgEsfAlg::create();
+        dut.data.bitfield = 0x0000_0001;
+        assert!(!dut.data.auto_alignment());
+
+        dut.data.set_auto_alignment(true);
+        assert_eq!(dut.data.bitfield, 0x0000_0101);
+        assert!(dut.data.auto_alignment());
+
+        dut.data.set_auto_alignment(false);
+        assert_eq!(dut.data.bitfield, 0x0000_0001);
+    }
+
     #[test]
     fn serialize() {
         let mut dut = UbxCfgEsfAlg::create();
         assert_eq!(dut.name, "UBX-CFG-ESFALG");
-        dut.data.yaw = 180 as u32 * 100;
-        dut.data.pitch = -45 as i16 * 100;
-        dut.data.roll = 45 as i16 * 100;
+        dut.data.yaw = 180_u32 * 100;
+        dut.data.pitch = -45_i16 * 100;
+        dut.data.roll = 45_i16 * 100;
 
         let data = dut.to_bin();
         assert_eq!(
@@ -1,25 +1,43 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_repr::Serialize_repr;
 
 use crate::ubxlib::cid::UbxCID;
-use crate::ubxlib::frame::UbxFrameWithData;
+use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameInfo, UbxFramePoll, UbxFrameWithData};
 
 const CLS: u8 = 0x06;
 const ID: u8 = 0x2F;
 
-#[derive(Serialize_repr, Debug)]
+#[derive(Default, Serialize_repr, Debug, Clone, Copy, PartialEq)]
 #[repr(u8)]
 pub enum LeverArmType {
+    #[default]
     VRPtoAntenna = 0,
     VRPtoIMU = 1,
-    _IMUtoAntenna = 2,
-    _IMUtoVRP = 3,
-    _IMUtoCRP = 4,
+    IMUtoAntenna = 2,
+    IMUtoVRP = 3,
+    IMUtoCRP = 4,
 }
 
-impl Default for LeverArmType {
-    fn default() -> Self {
-        LeverArmType::VRPtoAntenna
+impl LeverArmType {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(LeverArmType::VRPtoAntenna),
+            1 => Some(LeverArmType::VRPtoIMU),
+            2 => Some(LeverArmType::IMUtoAntenna),
+            3 => Some(LeverArmType::IMUtoVRP),
+            4 => Some(LeverArmType::IMUtoCRP),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LeverArmType::VRPtoAntenna => "VRP to antenna",
+            LeverArmType::VRPtoIMU => "VRP to IMU",
+            LeverArmType::IMUtoAntenna => "IMU to antenna",
+            LeverArmType::IMUtoVRP => "IMU to VRP",
+            LeverArmType::IMUtoCRP => "IMU to CRP",
+        }
     }
 }
 
@@ -56,11 +74,142 @@ impl UbxCfgEsflaSet {
     }
 }
 
+pub struct UbxCfgEsflaPoll {}
+
+impl UbxCfgEsflaPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-CFG-ESFLA-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataHeader {
+    pub version: u8,
+    pub num_configs: u8,
+    pub res1: [u8; 2],
+}
+
+// Type is kept as raw value, firmware may report types unknown to us
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataLeverArm {
+    pub leverarm_type: u8,
+    pub res2: u8,
+    pub leverarm_x: i16, // cm
+    pub leverarm_y: i16, // cm
+    pub leverarm_z: i16, // cm
+}
+
+impl DataLeverArm {
+    pub fn armtype(&self) -> Option<LeverArmType> {
+        LeverArmType::from_u8(self.leverarm_type)
+    }
+}
+
+// Poll response, contains all lever arms known to the receiver
+#[derive(Default, Debug)]
+pub struct UbxCfgEsfla {
+    pub name: &'static str,
+    pub cid: UbxCID,
+    pub header: DataHeader,
+    pub arms: Vec<DataLeverArm>,
+}
+
+impl UbxCfgEsfla {
+    pub fn new() -> Self {
+        Self {
+            name: "UBX-CFG-ESFLA",
+            cid: UbxCID::new(CLS, ID),
+            ..Default::default()
+        }
+    }
+
+    pub fn find(&self, armtype: LeverArmType) -> Option<&DataLeverArm> {
+        self.arms.iter().find(|a| a.armtype() == Some(armtype))
+    }
+
+    pub fn load(&mut self, data: &[u8]) {
+        // First read header to get number of lever arm blocks that follow
+        let bytes = data.len();
+        assert!(bytes >= 4);
+        self.header = bincode::deserialize(&data[0..4]).unwrap();
+        assert!(bytes == 4 + 8 * self.header.num_configs as usize);
+
+        self.arms.clear();
+        let mut offset = 4;
+        let size = 8;
+        while offset < bytes {
+            let arm: DataLeverArm = bincode::deserialize(&data[offset..offset + size]).unwrap();
+            self.arms.push(arm);
+
+            offset += size;
+        }
+    }
+}
+
+impl UbxFrameInfo for UbxCfgEsfla {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn cid(&self) -> UbxCID {
+        self.cid
+    }
+}
+
+impl UbxFrameDeSerialize for UbxCfgEsfla {
+    fn from_bin(&mut self, data: &[u8]) {
+        self.load(data);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::ubxlib::frame::UbxFrameSerialize;
 
+    #[test]
+    fn poll() {
+        let dut = UbxCfgEsflaPoll::create();
+        assert_eq!(dut.name, "UBX-CFG-ESFLA-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x2F, 0, 0, 53, 165]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 28] = [
+            0x00, 3, 0, 0, // header, 3 lever arms
+            0, 0, 100, 0, 150, 0, 30, 0, // VRP to antenna 1.0;1.5;0.3
+            1, 0, 0x9c, 0xff, 0, 0, 10, 0, // VRP to IMU -1.0;0;0.1
+            9, 0, 0, 0, 0, 0, 0, 0, // unknown type
+        ];
+        let mut dut = UbxCfgEsfla::new();
+        assert_eq!(dut.name, "UBX-CFG-ESFLA");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.arms.len(), 3);
+        let arm = dut.find(LeverArmType::VRPtoAntenna).unwrap();
+        assert_eq!(
+            (arm.leverarm_x, arm.leverarm_y, arm.leverarm_z),
+            (100, 150, 30)
+        );
+        let arm = dut.find(LeverArmType::VRPtoIMU).unwrap();
+        assert_eq!(
+            (arm.leverarm_x, arm.leverarm_y, arm.leverarm_z),
+            (-100, 0, 10)
+        );
+        assert!(dut.find(LeverArmType::IMUtoCRP).is_none());
+        assert!(dut.arms[2].armtype().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 8] = [0x00, 1, 0, 0, 0, 0, 100, 0];
+        let mut dut = UbxCfgEsfla::new();
+        dut.from_bin(&DATA);
+    }
+
     #[test]
     fn positive_values() {
         let mut dut = UbxCfgEsflaSet::create();
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x06;
+const ID: u8 = 0x82;
+
+// flags1 bits
+pub const FLAG_USE_WT_SPEED: u8 = 0x10;
+pub const FLAG_DIR_PIN_POL: u8 = 0x20;
+
+pub struct UbxCfgEsfwtPoll {}
+
+impl UbxCfgEsfwtPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-CFG-ESFWT-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataCfgEsfwt {
+    pub version: u8,
+    pub flags1: u8, // bit 0: combine ticks, 4: use speed, 5: dir pin polarity, 6: use pin
+    pub flags2: u8, // bit 0..3: disable automatic estimation of parameters
+    pub res1: u8,
+    pub wt_factor: u32,      // 1e-6 m per tick
+    pub wt_quant_error: u32, // 1e-6 m
+    pub wt_count_max: u32,
+    pub wt_latency: u16, // ms
+    pub wt_frequency: u8,
+    pub flags3: u8,
+    pub speed_dead_band: u16, // cm/s
+    pub res2: [u8; 10],
+}
+
+impl DataCfgEsfwt {
+    pub fn flag(&self, mask: u8) -> bool {
+        self.flags1 & mask != 0
+    }
+
+    pub fn set_flag(&mut self, mask: u8, value: bool) {
+        if value {
+            self.flags1 |= mask;
+        } else {
+            self.flags1 &= !mask;
+        }
+    }
+}
+
+pub struct UbxCfgEsfwt {}
+
+impl UbxCfgEsfwt {
+    pub fn create() -> UbxFrameWithData<DataCfgEsfwt> {
+        UbxFrameWithData::new("UBX-CFG-ESFWT", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxCfgEsfwtPoll::create();
+        assert_eq!(dut.name, "UBX-CFG-ESFWT-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x82, 0, 0, 136, 158]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 32] = [
+            0, 0x31, 0x00, 0, // version, flags
+            0xA0, 0x86, 0x01, 0x00, // factor 0.1 m
+            0x10, 0x27, 0x00, 0x00, // quant error 0.01 m
+            0xFF, 0xFF, 0x00, 0x00, // count max
+            0x0A, 0x00, 20, 0, // latency 10 ms, 20 Hz
+            0x05, 0x00, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // dead band 5 cm/s
+        ];
+        let mut dut = UbxCfgEsfwt::create();
+        assert_eq!(dut.name, "UBX-CFG-ESFWT");
+        dut.from_bin(&DATA);
+
+        assert!(dut.data.flag(FLAG_USE_WT_SPEED));
+        assert!(dut.data.flag(FLAG_DIR_PIN_POL));
+        assert_eq!(dut.data.wt_factor, 100_000);
+        assert_eq!(dut.data.wt_quant_error, 10_000);
+        assert_eq!(dut.data.wt_count_max, 0xFFFF);
+        assert_eq!(dut.data.wt_latency, 10);
+        assert_eq!(dut.data.wt_frequency, 20);
+        assert_eq!(dut.data.speed_dead_band, 5);
+    }
+
+    #[test]
+    fn set() {
+        let mut dut = UbxCfgEsfwt::create();
+        dut.data.set_flag(FLAG_DIR_PIN_POL, true);
+        dut.data.set_flag(FLAG_USE_WT_SPEED, true);
+        dut.data.set_flag(FLAG_USE_WT_SPEED, false);
+        dut.data.wt_factor = 0x01020304;
+        dut.data.wt_latency = 0x0506;
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..6], [0xb5, 0x62, 0x06, 0x82, 32, 0]);
+        assert_eq!(msg[6..10], [0, 0x20, 0, 0]);
+        assert_eq!(msg[10..14], [0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(msg[22..24], [0x06, 0x05]);
+    }
+}
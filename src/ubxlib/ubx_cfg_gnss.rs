@@ -43,7 +43,7 @@ impl UbxFrameInfo for UbxCfgGnssPoll {
 
 impl UbxFrameSerialize for UbxCfgGnssPoll {
     fn to_bin(&self) -> Vec<u8> {
-        UbxFrame::bytes(UbxCID::new(CLS, ID), &[].to_vec())
+        UbxFrame::bytes(UbxCID::new(CLS, ID), [].as_ref())
     }
 }
 
@@ -109,7 +109,7 @@ impl UbxCfgGnss {
 
         // Then read configuration blocks
         if bytes > 8 {
-            assert!((bytes - 4) % 8 == 0);
+            assert!((bytes - 4).is_multiple_of(8));
 
             let mut offset = 4;
             let size = 8;
@@ -158,7 +158,7 @@ impl UbxFrameSerialize for UbxCfgGnss {
 
 impl UbxFrameDeSerialize for UbxCfgGnss {
     fn from_bin(&mut self, data: &[u8]) {
-        self.load(&data);
+        self.load(data);
     }
 }
 
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x06;
+const ID: u8 = 0x5C;
+
+pub struct UbxCfgHnrPoll {}
+
+impl UbxCfgHnrPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-CFG-HNR-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataCfgHnr {
+    pub high_nav_rate: u8, // Hz
+    pub res1: [u8; 3],
+}
+
+pub struct UbxCfgHnr {}
+
+impl UbxCfgHnr {
+    pub fn create() -> UbxFrameWithData<DataCfgHnr> {
+        UbxFrameWithData::new("UBX-CFG-HNR", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxCfgHnrPoll::create();
+        assert_eq!(dut.name, "UBX-CFG-HNR-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x5C, 0, 0, 98, 44]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 4] = [30, 0, 0, 0];
+        let mut dut = UbxCfgHnr::create();
+        assert_eq!(dut.name, "UBX-CFG-HNR");
+        dut.from_bin(&DATA);
+        assert_eq!(dut.data.high_nav_rate, 30);
+    }
+
+    #[test]
+    fn set() {
+        let mut dut = UbxCfgHnr::create();
+        dut.data.high_nav_rate = 20;
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..10], [0xb5, 0x62, 0x06, 0x5C, 4, 0, 20, 0, 0, 0]);
+    }
+}
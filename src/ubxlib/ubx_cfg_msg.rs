@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x06;
+const ID: u8 = 0x01;
+
+// Message names as used in config files, with their class and id
+const MESSAGES: [(&str, u8, u8); 48] = [
+    ("NMEA-GGA", 0xF0, 0x00),
+    ("NMEA-GLL", 0xF0, 0x01),
+    ("NMEA-GSA", 0xF0, 0x02),
+    ("NMEA-GSV", 0xF0, 0x03),
+    ("NMEA-RMC", 0xF0, 0x04),
+    ("NMEA-VTG", 0xF0, 0x05),
+    ("NMEA-GRS", 0xF0, 0x06),
+    ("NMEA-GST", 0xF0, 0x07),
+    ("NMEA-ZDA", 0xF0, 0x08),
+    ("NMEA-GBS", 0xF0, 0x09),
+    ("NMEA-DTM", 0xF0, 0x0A),
+    ("NMEA-GNS", 0xF0, 0x0D),
+    ("NMEA-VLW", 0xF0, 0x0F),
+    ("PUBX-POSITION", 0xF1, 0x00),
+    ("PUBX-SVSTATUS", 0xF1, 0x03),
+    ("PUBX-TIME", 0xF1, 0x04),
+    ("UBX-NAV-POSECEF", 0x01, 0x01),
+    ("UBX-NAV-POSLLH", 0x01, 0x02),
+    ("UBX-NAV-STATUS", 0x01, 0x03),
+    ("UBX-NAV-DOP", 0x01, 0x04),
+    ("UBX-NAV-ATT", 0x01, 0x05),
+    ("UBX-NAV-SOL", 0x01, 0x06),
+    ("UBX-NAV-PVT", 0x01, 0x07),
+    ("UBX-NAV-ODO", 0x01, 0x09),
+    ("UBX-NAV-VELECEF", 0x01, 0x11),
+    ("UBX-NAV-VELNED", 0x01, 0x12),
+    ("UBX-NAV-HPPOSECEF", 0x01, 0x13),
+    ("UBX-NAV-HPPOSLLH", 0x01, 0x14),
+    ("UBX-NAV-TIMEGPS", 0x01, 0x20),
+    ("UBX-NAV-TIMEUTC", 0x01, 0x21),
+    ("UBX-NAV-CLOCK", 0x01, 0x22),
+    ("UBX-NAV-TIMELS", 0x01, 0x26),
+    ("UBX-NAV-SAT", 0x01, 0x35),
+    ("UBX-NAV-EOE", 0x01, 0x61),
+    ("UBX-RXM-SFRBX", 0x02, 0x13),
+    ("UBX-RXM-RAWX", 0x02, 0x15),
+    ("UBX-MON-HW", 0x0A, 0x09),
+    ("UBX-TIM-TP", 0x0D, 0x01),
+    ("UBX-TIM-TM2", 0x0D, 0x03),
+    ("UBX-TIM-SVIN", 0x0D, 0x04),
+    ("UBX-ESF-MEAS", 0x10, 0x02),
+    ("UBX-ESF-RAW", 0x10, 0x03),
+    ("UBX-ESF-STATUS", 0x10, 0x10),
+    ("UBX-ESF-ALG", 0x10, 0x14),
+    ("UBX-ESF-INS", 0x10, 0x15),
+    ("UBX-HNR-PVT", 0x28, 0x00),
+    ("UBX-HNR-ATT", 0x28, 0x01),
+    ("UBX-HNR-INS", 0x28, 0x02),
+];
+
+// Lookup message class and id by name, e.g. "NMEA-GSV" or "UBX-NAV-PVT"
+pub fn message_cid(name: &str) -> Option<UbxCID> {
+    let name = name.to_uppercase();
+    MESSAGES
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, cls, id)| UbxCID::new(*cls, *id))
+}
+
+#[derive(Default, Debug, Serialize)]
+pub struct DataCfgMsgPoll {
+    pub msg_class: u8,
+    pub msg_id: u8,
+}
+
+pub struct UbxCfgMsgPoll {}
+
+impl UbxCfgMsgPoll {
+    pub fn create(msg: UbxCID) -> UbxFrameWithData<DataCfgMsgPoll> {
+        UbxFrameWithData::init(
+            "UBX-CFG-MSG-POLL",
+            UbxCID::new(CLS, ID),
+            DataCfgMsgPoll {
+                msg_class: msg.cls(),
+                msg_id: msg.id(),
+            },
+        )
+    }
+}
+
+// Rates are given per port, index is the port id (see ubx_cfg_prt::PortId)
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataCfgMsg {
+    pub msg_class: u8,
+    pub msg_id: u8,
+    pub rates: [u8; 6],
+}
+
+pub struct UbxCfgMsg {}
+
+impl UbxCfgMsg {
+    pub fn create() -> UbxFrameWithData<DataCfgMsg> {
+        UbxFrameWithData::new("UBX-CFG-MSG", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxCfgMsgPoll::create(UbxCID::new(0x01, 0x07));
+        assert_eq!(dut.name, "UBX-CFG-MSG-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x01, 2, 0, 0x01, 0x07, 17, 58]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 8] = [0xF0, 0x03, 0, 1, 0, 1, 0, 0];
+        let mut dut = UbxCfgMsg::create();
+        assert_eq!(dut.name, "UBX-CFG-MSG");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.msg_class, 0xF0);
+        assert_eq!(dut.data.msg_id, 0x03);
+        assert_eq!(dut.data.rates, [0, 1, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn set() {
+        let mut dut = UbxCfgMsg::create();
+        dut.data.msg_class = 0x10;
+        dut.data.msg_id = 0x15;
+        dut.data.rates[1] = 1;
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..6], [0xb5, 0x62, 0x06, 0x01, 8, 0]);
+        assert_eq!(msg[6..14], [0x10, 0x15, 0, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(message_cid("NMEA-GSV"), Some(UbxCID::new(0xF0, 0x03)));
+        assert_eq!(message_cid("ubx-nav-pvt"), Some(UbxCID::new(0x01, 0x07)));
+        assert_eq!(message_cid("UBX-ESF-INS"), Some(UbxCID::new(0x10, 0x15)));
+        assert_eq!(message_cid("UBX-NAV-XYZ"), None);
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x06;
+const ID: u8 = 0x23;
+
+// mask1 bits, select parameters to apply
+pub const MASK1_MIN_MAX: u16 = 0x0004;
+pub const MASK1_MIN_CNO: u16 = 0x0008;
+pub const MASK1_INITIAL_3D_FIX: u16 = 0x0040;
+pub const MASK1_WKN_ROLL: u16 = 0x0200;
+pub const MASK1_ACK_AID: u16 = 0x0400;
+pub const MASK1_PPP: u16 = 0x2000;
+pub const MASK1_AOP: u16 = 0x4000;
+
+// aop_cfg bits
+pub const AOP_CFG_USE_AOP: u8 = 0x01;
+
+pub struct UbxCfgNavx5Poll {}
+
+impl UbxCfgNavx5Poll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-CFG-NAVX5-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataCfgNavx5 {
+    pub version: u16,
+    pub mask1: u16,
+    pub mask2: u32,
+    pub res1: [u8; 2],
+    pub min_svs: u8,
+    pub max_svs: u8,
+    pub min_cno: u8, // dBHz
+    pub res2: u8,
+    pub ini_fix_3d: u8,
+    pub res3: [u8; 2],
+    pub ack_aiding: u8, // 1: acknowledge MGA messages with UBX-MGA-ACK
+    pub wkn_rollover: u16,
+    pub sig_atten_comp_mode: u8,
+    pub res4: u8,
+    pub res5: [u8; 2],
+    pub res6: [u8; 2],
+    pub use_ppp: u8,
+    pub aop_cfg: u8, // bit 0: AssistNow Autonomous enabled
+    pub res7: [u8; 2],
+    pub aop_orb_max_err: u16, // m
+    pub res8: [u8; 4],
+    pub res9: [u8; 3],
+    pub use_adr: u8,
+}
+
+impl DataCfgNavx5 {
+    pub fn aop_enabled(&self) -> bool {
+        self.aop_cfg & AOP_CFG_USE_AOP != 0
+    }
+}
+
+pub struct UbxCfgNavx5 {}
+
+impl UbxCfgNavx5 {
+    pub fn create() -> UbxFrameWithData<DataCfgNavx5> {
+        UbxFrameWithData::new("UBX-CFG-NAVX5", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxCfgNavx5Poll::create();
+        assert_eq!(dut.name, "UBX-CFG-NAVX5-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x23, 0, 0, 41, 129]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 40] = [
+            2, 0, 0xff, 0xff, 0x7f, 0, 0, 0, // version, mask1, mask2
+            0, 0, 3, 32, 6, 0, 0, 0, 0, 1, // min/max svs, min cno, ack aiding
+            0x3a, 0x08, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, // week rollover 2106, aop
+            100, 0, 0, 0, 0, 0, 0, 0, 0, 1, // orbit max error 100 m, use adr
+        ];
+        let mut dut = UbxCfgNavx5::create();
+        assert_eq!(dut.name, "UBX-CFG-NAVX5");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.version, 2);
+        assert_eq!(dut.data.min_svs, 3);
+        assert_eq!(dut.data.max_svs, 32);
+        assert_eq!(dut.data.min_cno, 6);
+        assert_eq!(dut.data.ack_aiding, 1);
+        assert_eq!(dut.data.wkn_rollover, 2106);
+        assert_eq!(dut.data.aop_cfg, 1);
+        assert!(dut.data.aop_enabled());
+        assert_eq!(dut.data.aop_orb_max_err, 100);
+        assert_eq!(dut.data.use_adr, 1);
+    }
+
+    #[test]
+    fn set() {
+        let mut dut = UbxCfgNavx5::create();
+        dut.data.version = 2;
+        dut.data.mask1 = MASK1_ACK_AID;
+        dut.data.ack_aiding = 1;
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..6], [0xb5, 0x62, 0x06, 0x23, 40, 0]);
+        assert_eq!(msg[6..10], [2, 0, 0x00, 0x04]);
+        assert_eq!(msg[23], 1);
+    }
+
+    #[test]
+    fn set_aop() {
+        let mut dut = UbxCfgNavx5::create();
+        dut.data.version = 2;
+        dut.data.mask1 = MASK1_AOP | MASK1_MIN_MAX;
+        dut.data.min_svs = 4;
+        dut.data.max_svs = 20;
+        dut.data.aop_cfg = AOP_CFG_USE_AOP;
+        dut.data.aop_orb_max_err = 50;
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[6..10], [2, 0, 0x04, 0x40]);
+        assert_eq!(msg[16..18], [4, 20]);
+        assert_eq!(msg[33], 1);
+        assert_eq!(msg[36..38], [50, 0]);
+    }
+}
@@ -6,7 +6,66 @@ use crate::ubxlib::frame::UbxFrameWithData;
 const CLS: u8 = 0x06;
 const ID: u8 = 0x00;
 
-const PORTID_UART: u8 = 1;
+// Baudrates supported by the receiver UARTs
+pub const BAUDRATES: [u32; 9] = [
+    4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
+];
+
+// Protocol mask bits for in_proto_mask/out_proto_mask
+pub const PROTO_UBX: u16 = 0x0001;
+pub const PROTO_NMEA: u16 = 0x0002;
+pub const PROTO_RTCM2: u16 = 0x0004;
+pub const PROTO_RTCM3: u16 = 0x0020;
+
+const PROTOCOLS: [(&str, u16); 4] = [
+    ("ubx", PROTO_UBX),
+    ("nmea", PROTO_NMEA),
+    ("rtcm2", PROTO_RTCM2),
+    ("rtcm3", PROTO_RTCM3),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PortId {
+    Ddc = 0,
+    Uart1 = 1,
+    Uart2 = 2,
+    Usb = 3,
+    Spi = 4,
+}
+
+impl PortId {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "i2c" | "ddc" => Some(PortId::Ddc),
+            "uart1" => Some(PortId::Uart1),
+            "uart2" => Some(PortId::Uart2),
+            "usb" => Some(PortId::Usb),
+            "spi" => Some(PortId::Spi),
+            _ => None,
+        }
+    }
+}
+
+// Convert protocol list of form "ubx;nmea" to protocol mask
+pub fn protocols_from_str(text: &str) -> Option<u16> {
+    let mut mask = 0;
+    for name in text.split(';') {
+        let name = name.trim().to_lowercase();
+        let (_, bit) = PROTOCOLS.iter().find(|(n, _)| *n == name)?;
+        mask |= bit;
+    }
+    Some(mask)
+}
+
+// Convert protocol mask to list of form "UBX;NMEA"
+pub fn protocols_to_string(mask: u16) -> String {
+    let names: Vec<String> = PROTOCOLS
+        .iter()
+        .filter(|(_, bit)| mask & bit != 0)
+        .map(|(name, _)| name.to_uppercase())
+        .collect();
+    names.join(";")
+}
 
 #[derive(Default, Debug, Serialize)]
 pub struct DataCfgPrtPoll {
@@ -14,9 +73,9 @@ pub struct DataCfgPrtPoll {
 }
 
 impl DataCfgPrtPoll {
-    pub fn new() -> Self {
+    pub fn new(port: PortId) -> Self {
         Self {
-            port_id: PORTID_UART,
+            port_id: port as u8,
         }
     }
 }
@@ -25,10 +84,14 @@ pub struct UbxCfgPrtPoll {}
 
 impl UbxCfgPrtPoll {
     pub fn create() -> UbxFrameWithData<DataCfgPrtPoll> {
+        UbxCfgPrtPoll::port(PortId::Uart1)
+    }
+
+    pub fn port(port: PortId) -> UbxFrameWithData<DataCfgPrtPoll> {
         UbxFrameWithData::init(
             "UBX-CFG-PRT-POLL",
             UbxCID::new(CLS, ID),
-            DataCfgPrtPoll::new(),
+            DataCfgPrtPoll::new(port),
         )
     }
 }
@@ -47,9 +110,9 @@ pub struct DataCfgPrt {
     pub res2: [u8; 2],
 }
 
-pub struct UbxCfgPrtUart {}
+pub struct UbxCfgPrt {}
 
-impl UbxCfgPrtUart {
+impl UbxCfgPrt {
     pub fn create() -> UbxFrameWithData<DataCfgPrt> {
         UbxFrameWithData::new("UBX-CFG-PRT", UbxCID::new(CLS, ID))
     }
@@ -69,12 +132,20 @@ mod tests {
         assert_eq!(msg, [0xb5, 0x62, 0x06, 0x00, 1, 0, 1, 8, 34]);
     }
 
+    #[test]
+    fn poll_port() {
+        let dut = UbxCfgPrtPoll::port(PortId::Usb);
+        assert_eq!(dut.data.port_id, 3);
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..7], [0xb5, 0x62, 0x06, 0x00, 1, 0, 3]);
+    }
+
     #[test]
     fn deserialize() {
         const DATA: [u8; 20] = [
             1, 0, 0, 0, 192, 8, 0, 0, 128, 37, 0, 0, 7, 0, 3, 0, 0, 0, 0, 0,
         ];
-        let mut dut = UbxCfgPrtUart::create();
+        let mut dut = UbxCfgPrt::create();
         assert_eq!(dut.name, "UBX-CFG-PRT");
         dut.from_bin(&DATA);
 
@@ -86,11 +157,48 @@ mod tests {
 
     #[test]
     fn set() {
-        let mut dut = UbxCfgPrtUart::create();
+        let mut dut = UbxCfgPrt::create();
         assert_eq!(dut.name, "UBX-CFG-PRT");
         dut.data.baudrate = 115200;
         let msg = dut.to_bin();
         assert_eq!(msg[0..6], [0xb5, 0x62, 0x06, 0x00, 20, 0]);
         assert_eq!(msg[6 + 8..6 + 12], [0, 194, 1, 0]);
     }
+
+    #[test]
+    fn set_protocols() {
+        let mut dut = UbxCfgPrt::create();
+        dut.data.port_id = PortId::Uart2 as u8;
+        dut.data.in_proto_mask = PROTO_UBX | PROTO_RTCM3;
+        dut.data.out_proto_mask = PROTO_NMEA;
+        let msg = dut.to_bin();
+        assert_eq!(msg[6], 2);
+        assert_eq!(msg[6 + 12..6 + 16], [0x21, 0, 0x02, 0]);
+    }
+
+    #[test]
+    fn port_names() {
+        assert_eq!(PortId::from_name("i2c"), Some(PortId::Ddc));
+        assert_eq!(PortId::from_name("uart1"), Some(PortId::Uart1));
+        assert_eq!(PortId::from_name("uart2"), Some(PortId::Uart2));
+        assert_eq!(PortId::from_name("usb"), Some(PortId::Usb));
+        assert_eq!(PortId::from_name("spi"), Some(PortId::Spi));
+        assert_eq!(PortId::from_name("uart3"), None);
+    }
+
+    #[test]
+    fn protocol_names() {
+        assert_eq!(protocols_from_str("ubx"), Some(PROTO_UBX));
+        assert_eq!(protocols_from_str("UBX;nmea"), Some(PROTO_UBX | PROTO_NMEA));
+        assert_eq!(
+            protocols_from_str("rtcm2; rtcm3"),
+            Some(PROTO_RTCM2 | PROTO_RTCM3)
+        );
+        assert_eq!(protocols_from_str("ubx;foo"), None);
+        assert_eq!(protocols_from_str(""), None);
+
+        assert_eq!(protocols_to_string(PROTO_UBX | PROTO_NMEA), "UBX;NMEA");
+        assert_eq!(protocols_to_string(PROTO_RTCM3), "RTCM3");
+        assert_eq!(protocols_to_string(0), "");
+    }
 }
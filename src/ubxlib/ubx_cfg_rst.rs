@@ -7,23 +7,21 @@ use crate::ubxlib::frame::UbxFrameWithData;
 const CLS: u8 = 0x06;
 const ID: u8 = 0x04;
 
-#[derive(Serialize_repr, Debug)]
+#[derive(Default, Serialize_repr, Debug)]
 #[repr(u16)]
+#[allow(clippy::enum_variant_names)]
 pub enum BbrMask {
     HotStart = 0x0000,
     _WarmStart = 0x0001,
+    SensorFusion = 0x0800, // sensor fusion (dead reckoning) data
+    #[default]
     ColdStart = 0xFFFF,
 }
 
-impl Default for BbrMask {
-    fn default() -> Self {
-        BbrMask::ColdStart
-    }
-}
-
-#[derive(Serialize_repr, Debug)]
+#[derive(Default, Serialize_repr, Debug)]
 #[repr(u8)]
 pub enum ResetMode {
+    #[default]
     ImmediateHwReset = 0x00,
     SwReset = 0x01,
     _HwReset = 0x04,
@@ -31,12 +29,6 @@ pub enum ResetMode {
     _Start = 0x09,
 }
 
-impl Default for ResetMode {
-    fn default() -> Self {
-        ResetMode::ImmediateHwReset
-    }
-}
-
 #[derive(Default, Debug, Serialize)]
 pub struct DataCfgRst {
     pub nav_bbr_mask: BbrMask,
@@ -65,6 +57,15 @@ impl UbxCfgRstAction {
         )
     }
 
+    // Clears learned sensor calibration, navigation data is kept
+    pub fn clear_sensor_fusion() -> UbxFrameWithData<DataCfgRst> {
+        UbxFrameWithData::init(
+            "UBX-CFG-RST",
+            UbxCID::new(CLS, ID),
+            DataCfgRst::from(BbrMask::SensorFusion, ResetMode::SwReset),
+        )
+    }
+
     pub fn stop() -> UbxFrameWithData<DataCfgRst> {
         UbxFrameWithData::init(
             "UBX-CFG-RST",
@@ -90,6 +91,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clear_sensor_fusion() {
+        let dut = UbxCfgRstAction::clear_sensor_fusion();
+        let msg = dut.to_bin();
+        assert_eq!(
+            msg,
+            [0xb5, 0x62, 0x06, 0x04, 4, 0, 0x00, 0x08, 0x01, 0, 23, 126]
+        );
+    }
+
     #[test]
     fn stop() {
         let dut = UbxCfgRstAction::stop();
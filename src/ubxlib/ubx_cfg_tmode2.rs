@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x06;
+const ID: u8 = 0x3D;
+
+// flags bits
+pub const FLAG_LLA: u16 = 0x0001; // position given as lat/lon/alt instead of ECEF
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeModeType {
+    Disabled = 0,
+    SurveyIn = 1,
+    Fixed = 2,
+}
+
+impl TimeModeType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "disabled" => Some(TimeModeType::Disabled),
+            "survey-in" => Some(TimeModeType::SurveyIn),
+            "fixed" => Some(TimeModeType::Fixed),
+            _ => None,
+        }
+    }
+}
+
+pub struct UbxCfgTmode2Poll {}
+
+impl UbxCfgTmode2Poll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-CFG-TMODE2-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataCfgTmode2 {
+    pub time_mode: u8,
+    pub res1: u8,
+    pub flags: u16,
+    pub ecef_x_or_lat: i32,  // cm or 1e-7°
+    pub ecef_y_or_lon: i32,  // cm or 1e-7°
+    pub ecef_z_or_alt: i32,  // cm
+    pub fixed_pos_acc: u32,  // mm
+    pub svin_min_dur: u32,   // s
+    pub svin_acc_limit: u32, // mm
+}
+
+impl DataCfgTmode2 {
+    pub fn mode_name(&self) -> &'static str {
+        match self.time_mode {
+            0 => "disabled",
+            1 => "survey-in",
+            2 => "fixed",
+            _ => "unknown",
+        }
+    }
+
+    pub fn lla(&self) -> bool {
+        self.flags & FLAG_LLA != 0
+    }
+}
+
+pub struct UbxCfgTmode2 {}
+
+impl UbxCfgTmode2 {
+    pub fn create() -> UbxFrameWithData<DataCfgTmode2> {
+        UbxFrameWithData::new("UBX-CFG-TMODE2", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxCfgTmode2Poll::create();
+        assert_eq!(dut.name, "UBX-CFG-TMODE2-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x3d, 0, 0, 67, 207]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 28] = [
+            2, 0, 0, 0, // fixed, ECEF
+            0x5c, 0x59, 0x9b, 0x18, // x 4128341.40 m
+            0xfd, 0x8f, 0xb4, 0x04, // y 789422.05 m
+            0x8f, 0x52, 0x6a, 0x1b, // z 4599527.83 m
+            0xd0, 0x07, 0, 0, // accuracy 2 m
+            0x2c, 0x01, 0, 0, // min duration 300 s
+            0, 0, 0, 0, // accuracy limit
+        ];
+        let mut dut = UbxCfgTmode2::create();
+        assert_eq!(dut.name, "UBX-CFG-TMODE2");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.mode_name(), "fixed");
+        assert!(!dut.data.lla());
+        assert_eq!(dut.data.ecef_x_or_lat, 412_834_140);
+        assert_eq!(dut.data.ecef_y_or_lon, 78_942_205);
+        assert_eq!(dut.data.ecef_z_or_alt, 459_952_783);
+        assert_eq!(dut.data.fixed_pos_acc, 2000);
+        assert_eq!(dut.data.svin_min_dur, 300);
+    }
+
+    #[test]
+    fn set() {
+        let mut dut = UbxCfgTmode2::create();
+        dut.data.time_mode = TimeModeType::SurveyIn as u8;
+        dut.data.svin_min_dur = 300;
+        dut.data.svin_acc_limit = 2000;
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..7], [0xb5, 0x62, 0x06, 0x3d, 28, 0, 1]);
+        assert_eq!(msg[26..34], [0x2c, 0x01, 0, 0, 0xd0, 0x07, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 27] = [0; 27];
+        let mut dut = UbxCfgTmode2::create();
+        dut.from_bin(&DATA);
+    }
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x06;
+const ID: u8 = 0x31;
+
+// flags bits
+pub const FLAG_ACTIVE: u32 = 0x0001;
+pub const FLAG_LOCK_GNSS_FREQ: u32 = 0x0002; // synchronize to GNSS when locked
+pub const FLAG_LOCKED_OTHER_SET: u32 = 0x0004; // use locked values when locked
+pub const FLAG_IS_FREQ: u32 = 0x0008; // frequency in Hz instead of period in us
+pub const FLAG_IS_LENGTH: u32 = 0x0010; // pulse length in us instead of duty cycle
+pub const FLAG_ALIGN_TO_TOW: u32 = 0x0020;
+pub const FLAG_POLARITY: u32 = 0x0040; // rising edge at top of second
+const GRID_SHIFT: u32 = 7;
+const GRID_MASK: u32 = 0x0780;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimePulse {
+    Tp1 = 0,
+    Tp2 = 1,
+}
+
+impl TimePulse {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tp1" => Some(TimePulse::Tp1),
+            "tp2" => Some(TimePulse::Tp2),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimePulse::Tp1 => "TIMEPULSE",
+            TimePulse::Tp2 => "TIMEPULSE2",
+        }
+    }
+}
+
+// Time grid the pulse is aligned to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeGrid {
+    Utc = 0,
+    Gps = 1,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataCfgTp5Poll {
+    pub tp_idx: u8,
+}
+
+pub struct UbxCfgTp5Poll {}
+
+impl UbxCfgTp5Poll {
+    pub fn pulse(pulse: TimePulse) -> UbxFrameWithData<DataCfgTp5Poll> {
+        UbxFrameWithData::init(
+            "UBX-CFG-TP5-POLL",
+            UbxCID::new(CLS, ID),
+            DataCfgTp5Poll {
+                tp_idx: pulse as u8,
+            },
+        )
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataCfgTp5 {
+    pub tp_idx: u8,
+    pub version: u8,
+    pub res1: [u8; 2],
+    pub ant_cable_delay: i16, // ns
+    pub rf_group_delay: i16,  // ns
+    pub freq_period: u32,     // Hz or us
+    pub freq_period_lock: u32,
+    pub pulse_len_ratio: u32, // us or 2^-32
+    pub pulse_len_ratio_lock: u32,
+    pub user_config_delay: i32, // ns
+    pub flags: u32,
+}
+
+impl DataCfgTp5 {
+    pub fn flag(&self, mask: u32) -> bool {
+        self.flags & mask != 0
+    }
+
+    pub fn set_flag(&mut self, mask: u32, value: bool) {
+        if value {
+            self.flags |= mask;
+        } else {
+            self.flags &= !mask;
+        }
+    }
+
+    pub fn grid(&self) -> u32 {
+        (self.flags & GRID_MASK) >> GRID_SHIFT
+    }
+
+    pub fn set_grid(&mut self, grid: TimeGrid) {
+        self.flags = (self.flags & !GRID_MASK) | ((grid as u32) << GRID_SHIFT);
+    }
+
+    pub fn grid_name(&self) -> &'static str {
+        match self.grid() {
+            0 => "UTC",
+            1 => "GPS",
+            2 => "GLONASS",
+            3 => "BeiDou",
+            4 => "Galileo",
+            _ => "unknown",
+        }
+    }
+}
+
+pub struct UbxCfgTp5 {}
+
+impl UbxCfgTp5 {
+    pub fn create() -> UbxFrameWithData<DataCfgTp5> {
+        UbxFrameWithData::new("UBX-CFG-TP5", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxCfgTp5Poll::pulse(TimePulse::Tp2);
+        assert_eq!(dut.name, "UBX-CFG-TP5-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x06, 0x31, 1, 0, 1, 57, 230]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 32] = [
+            0, 1, 0, 0, // TIMEPULSE, version 1
+            50, 0, 0, 0, // cable delay 50 ns, rf delay 0 ns
+            0x40, 0x42, 0x0f, 0, // period 1 s
+            0x40, 0x42, 0x0f, 0, // locked period 1 s
+            0, 0, 0, 0, // unlocked length 0 us
+            0xa0, 0x86, 0x01, 0, // locked length 100 ms
+            0, 0, 0, 0, // user delay
+            0x77, 0, 0, 0, // active, lock, other set, length, align, rising, UTC
+        ];
+        let mut dut = UbxCfgTp5::create();
+        assert_eq!(dut.name, "UBX-CFG-TP5");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.ant_cable_delay, 50);
+        assert_eq!(dut.data.freq_period, 1_000_000);
+        assert_eq!(dut.data.pulse_len_ratio_lock, 100_000);
+        assert!(dut.data.flag(FLAG_ACTIVE));
+        assert!(!dut.data.flag(FLAG_IS_FREQ));
+        assert!(dut.data.flag(FLAG_IS_LENGTH));
+        assert!(dut.data.flag(FLAG_POLARITY));
+        assert_eq!(dut.data.grid_name(), "UTC");
+    }
+
+    #[test]
+    fn set() {
+        let mut dut = UbxCfgTp5::create();
+        dut.data.tp_idx = 1;
+        dut.data.version = 1;
+        dut.data.freq_period = 10_000_000;
+        dut.data.set_flag(FLAG_ACTIVE | FLAG_IS_FREQ, true);
+        dut.data.set_grid(TimeGrid::Gps);
+        assert_eq!(dut.data.grid_name(), "GPS");
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..6], [0xb5, 0x62, 0x06, 0x31, 32, 0]);
+        assert_eq!(msg[6..8], [1, 1]);
+        assert_eq!(msg[14..18], [0x80, 0x96, 0x98, 0x00]);
+        assert_eq!(msg[34..38], [0x89, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 31] = [0; 31];
+        let mut dut = UbxCfgTp5::create();
+        dut.from_bin(&DATA);
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x10;
+const ID: u8 = 0x14;
+
+pub struct UbxEsfAlgPoll {}
+
+impl UbxEsfAlgPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-ESF-ALG-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataEsfAlg {
+    pub itow: u32,
+    pub version: u8,
+    pub flags: u8,  // bit 0: auto alignment on, bit 1..3: status
+    pub errors: u8, // bit 0: tilt error, bit 1: yaw error, bit 2: angle error
+    pub res1: u8,
+    pub yaw: u32,   // 1e-2, 0..360°
+    pub pitch: i16, // 1e-2, -90..90°
+    pub roll: i16,  // 1e-2, -180..180°
+}
+
+impl DataEsfAlg {
+    pub fn auto_alignment(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    pub fn status(&self) -> u8 {
+        (self.flags >> 1) & 0x07
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        match self.status() {
+            0 => "user-defined",
+            1 => "roll/pitch alignment",
+            2 => "roll/pitch/yaw alignment",
+            3 => "coarse",
+            4 => "fine",
+            _ => "unknown",
+        }
+    }
+
+    // Alignment has converged (coarse or fine angles in use)
+    pub fn aligned(&self) -> bool {
+        self.status() == 3 || self.status() == 4
+    }
+
+    pub fn errors_to_string(&self) -> String {
+        const ERRORS: [&str; 3] = ["tilt", "yaw", "angle"];
+        let names: Vec<&str> = ERRORS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.errors & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        names.join(";")
+    }
+}
+
+pub struct UbxEsfAlg {}
+
+impl UbxEsfAlg {
+    pub fn create() -> UbxFrameWithData<DataEsfAlg> {
+        UbxFrameWithData::new("UBX-ESF-ALG", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxEsfAlgPoll::create();
+        assert_eq!(dut.name, "UBX-ESF-ALG-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x10, 0x14, 0, 0, 36, 124]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 16] = [
+            0x10, 0x27, 0x00, 0x00, 1, 0x09, 0x02, 0, 0x50, 0x46, 0x00, 0x00, 0x6C, 0xEE, 0x94,
+            0x11,
+        ];
+        let mut dut = UbxEsfAlg::create();
+        assert_eq!(dut.name, "UBX-ESF-ALG");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.itow, 10000);
+        assert!(dut.data.auto_alignment());
+        assert_eq!(dut.data.status(), 4);
+        assert_eq!(dut.data.status_name(), "fine");
+        assert!(dut.data.aligned());
+        assert_eq!(dut.data.errors_to_string(), "yaw");
+        assert_eq!(dut.data.yaw, 18000);
+        assert_eq!(dut.data.pitch, -4500);
+        assert_eq!(dut.data.roll, 4500);
+    }
+
+    #[test]
+    fn not_aligned() {
+        const DATA: [u8; 16] = [0, 0, 0, 0, 1, 0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut dut = UbxEsfAlg::create();
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.status_name(), "roll/pitch/yaw alignment");
+        assert!(!dut.data.aligned());
+        assert_eq!(dut.data.errors_to_string(), "");
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x10;
+const ID: u8 = 0x15;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataEsfIns {
+    pub bitfield0: u32, // bit 8..13: x/y/z angular rate and acceleration valid
+    pub res1: [u8; 4],
+    pub itow: u32,
+    pub x_ang_rate: i32, // 1e-3 °/s
+    pub y_ang_rate: i32, // 1e-3 °/s
+    pub z_ang_rate: i32, // 1e-3 °/s
+    pub x_accel: i32,    // 1e-2 m/s²
+    pub y_accel: i32,    // 1e-2 m/s²
+    pub z_accel: i32,    // 1e-2 m/s²
+}
+
+impl DataEsfIns {
+    pub fn valid(&self) -> bool {
+        self.bitfield0 & 0x3F00 == 0x3F00
+    }
+}
+
+pub struct UbxEsfIns {}
+
+impl UbxEsfIns {
+    pub fn create() -> UbxFrameWithData<DataEsfIns> {
+        UbxFrameWithData::new("UBX-ESF-INS", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn deserialize() {
+        let mut data = [0u8; 36];
+        data[0..4].copy_from_slice(&0x3F00u32.to_le_bytes());
+        data[8..12].copy_from_slice(&5000u32.to_le_bytes());
+        data[20..24].copy_from_slice(&(-1234i32).to_le_bytes());
+        data[32..36].copy_from_slice(&981i32.to_le_bytes());
+
+        let mut dut = UbxEsfIns::create();
+        assert_eq!(dut.name, "UBX-ESF-INS");
+        dut.from_bin(&data);
+
+        assert!(dut.data.valid());
+        assert_eq!(dut.data.itow, 5000);
+        assert_eq!(dut.data.z_ang_rate, -1234);
+        assert_eq!(dut.data.z_accel, 981);
+    }
+}
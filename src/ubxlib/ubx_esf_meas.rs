@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFrame, UbxFrameDeSerialize, UbxFrameInfo, UbxFrameSerialize};
+use crate::ubxlib::ubx_esf_raw::SensorSample;
+
+const CLS: u8 = 0x10;
+const ID: u8 = 0x02;
+
+// Data types of host supplied measurements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeasType {
+    WheelTickFl = 6,
+    WheelTickFr = 7,
+    WheelTickRl = 8,
+    WheelTickRr = 9,
+    SingleTick = 10,
+    Speed = 11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub data_type: MeasType,
+    pub data_field: u32, // 24 bit
+}
+
+impl Measurement {
+    // Wheel ticks are an absolute 23 bit counter, bit 23 flags backward direction
+    pub fn ticks(data_type: MeasType, ticks: u32, backward: bool) -> Self {
+        let dir = if backward { 0x80_0000 } else { 0 };
+        Self {
+            data_type,
+            data_field: (ticks & 0x7F_FFFF) | dir,
+        }
+    }
+
+    // Speed in mm/s, signed 24 bit value (negative when driving backward)
+    pub fn speed(mm_per_s: i32) -> Self {
+        Self {
+            data_type: MeasType::Speed,
+            data_field: (mm_per_s as u32) & 0xFF_FFFF,
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        self.data_field & 0xFF_FFFF | (self.data_type as u32) << 24
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataHeader {
+    pub time_tag: u32, // ms
+    pub flags: u16,    // bit 11..15: number of measurements
+    pub id: u16,
+}
+
+#[derive(Default, Debug)]
+pub struct UbxEsfMeas {
+    pub name: &'static str,
+    pub cid: UbxCID,
+    pub header: DataHeader,
+    pub measurements: Vec<Measurement>, // to send
+    pub samples: Vec<SensorSample>,     // received
+    pub calib_ttag: Option<u32>,
+}
+
+impl UbxEsfMeas {
+    pub fn new(time_tag: u32) -> Self {
+        Self {
+            name: "UBX-ESF-MEAS",
+            cid: UbxCID::new(CLS, ID),
+            header: DataHeader {
+                time_tag,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn num_meas(&self) -> usize {
+        (self.header.flags >> 11) as usize
+    }
+
+    // Returns false if length doesn't match header, e.g. different firmware layout
+    pub fn load(&mut self, data: &[u8]) -> bool {
+        self.samples.clear();
+        self.calib_ttag = None;
+
+        let bytes = data.len();
+        if bytes < 8 {
+            return false;
+        }
+        self.header = bincode::deserialize(&data[0..8]).unwrap();
+
+        // Calibrated time tag follows measurements if flagged valid
+        let num_meas = self.num_meas();
+        let calib_valid = self.header.flags & 0x08 != 0;
+        let expected = 8 + 4 * num_meas + if calib_valid { 4 } else { 0 };
+        if bytes != expected {
+            return false;
+        }
+
+        for i in 0..num_meas {
+            let offset = 8 + 4 * i;
+            let word: u32 = bincode::deserialize(&data[offset..offset + 4]).unwrap();
+            self.samples.push(SensorSample::from_u32(word));
+        }
+
+        if calib_valid {
+            self.calib_ttag = Some(bincode::deserialize(&data[bytes - 4..]).unwrap());
+        }
+        true
+    }
+
+    fn save(&self) -> Vec<u8> {
+        // At most 31 measurements fit into numMeas field
+        assert!(self.measurements.len() < 32);
+
+        let header = DataHeader {
+            flags: (self.measurements.len() as u16) << 11,
+            ..self.header
+        };
+        let mut data = bincode::serialize(&header).unwrap();
+
+        for meas in &self.measurements {
+            let mut meas_data = bincode::serialize(&meas.to_u32()).unwrap();
+            data.append(&mut meas_data);
+        }
+        data
+    }
+}
+
+impl UbxFrameInfo for UbxEsfMeas {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn cid(&self) -> UbxCID {
+        self.cid
+    }
+}
+
+impl UbxFrameSerialize for UbxEsfMeas {
+    fn to_bin(&self) -> Vec<u8> {
+        let data = self.save();
+        UbxFrame::bytes(self.cid(), &data)
+    }
+}
+
+impl UbxFrameDeSerialize for UbxEsfMeas {
+    fn from_bin(&mut self, data: &[u8]) {
+        assert!(self.load(data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed() {
+        let mut dut = UbxEsfMeas::new(1000);
+        assert_eq!(dut.name, "UBX-ESF-MEAS");
+        dut.measurements.push(Measurement::speed(13890));
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..6], [0xb5, 0x62, 0x10, 0x02, 12, 0]);
+        assert_eq!(msg[6..14], [0xe8, 0x03, 0, 0, 0x00, 0x08, 0, 0]);
+        assert_eq!(msg[14..18], [0x42, 0x36, 0x00, 11]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 20] = [
+            0xe8, 0x03, 0, 0, 0x08, 0x10, 0, 0, // time tag 1000, 2 meas, calib ttag valid
+            0x64, 0x00, 0x80, 6, // wheel-fl 100 ticks backward
+            0x42, 0x36, 0x00, 11, // speed 13.89 m/s
+            0xd0, 0x07, 0, 0, // calib ttag 2000
+        ];
+        let mut dut = UbxEsfMeas::new(0);
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.header.time_tag, 1000);
+        assert_eq!(dut.num_meas(), 2);
+        assert_eq!(dut.samples[0].name(), "wheel-fl");
+        assert_eq!(dut.samples[0].value(), -100.0);
+        assert_eq!(dut.samples[1].name(), "speed");
+        assert_eq!(dut.calib_ttag, Some(2000));
+    }
+
+    #[test]
+    fn deserialize_no_calib_ttag() {
+        const DATA: [u8; 12] = [0, 0, 0, 0, 0x00, 0x08, 0, 0, 0x00, 0x04, 0x00, 16];
+        let mut dut = UbxEsfMeas::new(0);
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.samples.len(), 1);
+        assert_eq!(dut.samples[0].name(), "accel-x");
+        assert_eq!(dut.samples[0].value(), 1.0);
+        assert_eq!(dut.calib_ttag, None);
+    }
+
+    #[test]
+    fn wrong_length() {
+        // 2 measurements announced, only 1 present
+        const DATA: [u8; 12] = [0, 0, 0, 0, 0x00, 0x10, 0, 0, 0x00, 0x04, 0x00, 16];
+        let mut dut = UbxEsfMeas::new(0);
+        assert!(!dut.load(&DATA));
+        assert!(dut.samples.is_empty());
+        assert!(!dut.load(&DATA[0..4]));
+    }
+
+    #[test]
+    fn negative_speed() {
+        let mut dut = UbxEsfMeas::new(0);
+        dut.measurements.push(Measurement::speed(-1000));
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[14..18], [0x18, 0xfc, 0xff, 11]);
+    }
+
+    #[test]
+    fn wheel_ticks() {
+        let mut dut = UbxEsfMeas::new(0x01020304);
+        dut.measurements
+            .push(Measurement::ticks(MeasType::WheelTickRl, 0x123456, false));
+        dut.measurements
+            .push(Measurement::ticks(MeasType::WheelTickRr, 0xFFFFFFFF, true));
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..6], [0xb5, 0x62, 0x10, 0x02, 16, 0]);
+        assert_eq!(msg[6..14], [0x04, 0x03, 0x02, 0x01, 0x00, 0x10, 0, 0]);
+        assert_eq!(msg[14..18], [0x56, 0x34, 0x12, 8]);
+        assert_eq!(msg[18..22], [0xff, 0xff, 0xff, 9]);
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameInfo};
+
+const CLS: u8 = 0x10;
+const ID: u8 = 0x03;
+
+// Sensor names by data type, shared by ESF-RAW, ESF-MEAS and ESF-STATUS
+pub fn sensor_name(data_type: u8) -> &'static str {
+    match data_type {
+        5 => "gyro-z",
+        6 => "wheel-fl",
+        7 => "wheel-fr",
+        8 => "wheel-rl",
+        9 => "wheel-rr",
+        10 => "wheel-tick",
+        11 => "speed",
+        12 => "gyro-temp",
+        13 => "gyro-y",
+        14 => "gyro-x",
+        16 => "accel-x",
+        17 => "accel-y",
+        18 => "accel-z",
+        _ => "unknown",
+    }
+}
+
+// One sensor data word, bits 0..23: data field, bits 24..29: data type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorSample {
+    pub data_type: u8,
+    pub data_field: u32,
+}
+
+impl SensorSample {
+    pub fn from_u32(data: u32) -> Self {
+        Self {
+            data_type: ((data >> 24) & 0x3F) as u8,
+            data_field: data & 0xFF_FFFF,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        sensor_name(self.data_type)
+    }
+
+    // Value scaled to unit(), wheel ticks are negative when moving backward
+    pub fn value(&self) -> f64 {
+        // Sign extend 24 bit value
+        let signed = ((self.data_field << 8) as i32 >> 8) as f64;
+        match self.data_type {
+            5 | 13 | 14 => signed / 4096.0, // 2^-12 deg/s
+            16..=18 => signed / 1024.0,     // 2^-10 m/s²
+            12 => signed / 100.0,           // 1e-2 °C
+            11 => signed / 1000.0,          // 1e-3 m/s
+            6..=10 => {
+                let ticks = (self.data_field & 0x7F_FFFF) as f64;
+                if self.data_field & 0x80_0000 != 0 {
+                    -ticks
+                } else {
+                    ticks
+                }
+            }
+            _ => self.data_field as f64,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self.data_type {
+            5 | 13 | 14 => "deg/s",
+            16..=18 => "m/s^2",
+            12 => "degC",
+            11 => "m/s",
+            6..=10 => "ticks",
+            _ => "",
+        }
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataRawSample {
+    pub data: u32,
+    pub s_ttag: u32, // sensor time tag
+}
+
+#[derive(Default, Debug)]
+pub struct UbxEsfRaw {
+    pub name: &'static str,
+    pub cid: UbxCID,
+    pub samples: Vec<DataRawSample>,
+}
+
+impl UbxEsfRaw {
+    pub fn new() -> Self {
+        Self {
+            name: "UBX-ESF-RAW",
+            cid: UbxCID::new(CLS, ID),
+            ..Default::default()
+        }
+    }
+
+    // Returns false if length doesn't fit, e.g. different firmware layout
+    pub fn load(&mut self, data: &[u8]) -> bool {
+        // 4 reserved bytes followed by data/time tag pairs
+        self.samples.clear();
+        let bytes = data.len();
+        if bytes < 4 || !(bytes - 4).is_multiple_of(8) {
+            return false;
+        }
+
+        let mut offset = 4;
+        let size = 8;
+        while offset < bytes {
+            let sample: DataRawSample = bincode::deserialize(&data[offset..offset + size]).unwrap();
+            self.samples.push(sample);
+
+            offset += size;
+        }
+        true
+    }
+}
+
+impl UbxFrameInfo for UbxEsfRaw {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn cid(&self) -> UbxCID {
+        self.cid
+    }
+}
+
+impl UbxFrameDeSerialize for UbxEsfRaw {
+    fn from_bin(&mut self, data: &[u8]) {
+        assert!(self.load(data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 28] = [
+            0, 0, 0, 0, // reserved
+            0x00, 0x10, 0x00, 5, 0x10, 0x00, 0x00, 0x00, // gyro-z 1 deg/s
+            0x00, 0xD8, 0xFF, 18, 0x11, 0x00, 0x00, 0x00, // accel-z -10 m/s²
+            0xD2, 0x04, 0x00, 12, 0x12, 0x00, 0x00, 0x00, // gyro-temp 12.34 °C
+        ];
+        let mut dut = UbxEsfRaw::new();
+        assert_eq!(dut.name, "UBX-ESF-RAW");
+        dut.from_bin(&DATA);
+        assert_eq!(dut.samples.len(), 3);
+
+        let s = SensorSample::from_u32(dut.samples[0].data);
+        assert_eq!(s.name(), "gyro-z");
+        assert_eq!(s.value(), 1.0);
+        assert_eq!(s.unit(), "deg/s");
+        assert_eq!(dut.samples[0].s_ttag, 0x10);
+
+        let s = SensorSample::from_u32(dut.samples[1].data);
+        assert_eq!(s.name(), "accel-z");
+        assert_eq!(s.value(), -10.0);
+        assert_eq!(s.unit(), "m/s^2");
+
+        let s = SensorSample::from_u32(dut.samples[2].data);
+        assert_eq!(s.name(), "gyro-temp");
+        assert!((s.value() - 12.34).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wheel_ticks() {
+        let s = SensorSample::from_u32(0x0880_0064);
+        assert_eq!(s.name(), "wheel-rl");
+        assert_eq!(s.value(), -100.0);
+        assert_eq!(s.unit(), "ticks");
+
+        let s = SensorSample::from_u32(0x0B00_3642);
+        assert_eq!(s.name(), "speed");
+        assert!((s.value() - 13.89).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 8] = [0; 8];
+        let mut dut = UbxEsfRaw::new();
+        dut.from_bin(&DATA);
+    }
+
+    #[test]
+    fn load_wrong_length() {
+        let mut dut = UbxEsfRaw::new();
+        assert!(dut.load(&[0; 12]));
+        assert_eq!(dut.samples.len(), 1);
+        assert!(!dut.load(&[0; 8]));
+        assert!(dut.samples.is_empty());
+        assert!(!dut.load(&[0; 2]));
+    }
+}
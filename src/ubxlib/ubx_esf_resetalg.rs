@@ -0,0 +1,28 @@
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFramePoll;
+
+const CLS: u8 = 0x10;
+const ID: u8 = 0x13;
+
+// Command without payload, acknowledged by receiver
+pub struct UbxEsfResetAlgAction {}
+
+impl UbxEsfResetAlgAction {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-ESF-RESETALG", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameSerialize;
+
+    #[test]
+    fn reset() {
+        let dut = UbxEsfResetAlgAction::create();
+        assert_eq!(dut.name, "UBX-ESF-RESETALG");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x10, 0x13, 0, 0, 35, 121]);
+    }
+}
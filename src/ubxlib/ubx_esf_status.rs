@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameInfo, UbxFramePoll};
+use crate::ubxlib::ubx_esf_raw::sensor_name;
+
+const CLS: u8 = 0x10;
+const ID: u8 = 0x10;
+
+// fusion_mode values
+pub const FUSION_MODE_INIT: u8 = 0;
+pub const FUSION_MODE_FUSION: u8 = 1;
+pub const FUSION_MODE_SUSPENDED: u8 = 2;
+pub const FUSION_MODE_DISABLED: u8 = 3;
+
+pub struct UbxEsfStatusPoll {}
+
+impl UbxEsfStatusPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-ESF-STATUS-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataHeader {
+    pub itow: u32,
+    pub version: u8,
+    pub res1: [u8; 7],
+    pub fusion_mode: u8, // see FUSION_MODE_*
+    pub res2: [u8; 2],
+    pub num_sens: u8,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataSensor {
+    pub sens_status1: u8, // bit 0..5: type, bit 6: used, bit 7: ready
+    pub sens_status2: u8, // bit 0..1: calibration status, bit 2..3: time status
+    pub freq: u8,         // Hz
+    pub faults: u8,       // bit 0: bad meas, 1: bad ttag, 2: missing meas, 3: noisy meas
+}
+
+impl DataSensor {
+    pub fn sensor_type(&self) -> u8 {
+        self.sens_status1 & 0x3F
+    }
+
+    pub fn used(&self) -> bool {
+        self.sens_status1 & 0x40 != 0
+    }
+
+    pub fn ready(&self) -> bool {
+        self.sens_status1 & 0x80 != 0
+    }
+
+    pub fn calibrated(&self) -> bool {
+        self.sens_status2 & 0x03 >= 2
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        sensor_name(self.sensor_type())
+    }
+
+    pub fn calib_status_name(&self) -> &'static str {
+        match self.sens_status2 & 0x03 {
+            0 => "not calibrated",
+            1 => "calibrating",
+            _ => "calibrated",
+        }
+    }
+
+    pub fn faults_to_string(&self) -> String {
+        const FAULTS: [&str; 4] = ["bad-meas", "bad-ttag", "missing-meas", "noisy-meas"];
+        let names: Vec<&str> = FAULTS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.faults & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        names.join(";")
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct UbxEsfStatus {
+    pub name: &'static str,
+    pub cid: UbxCID,
+    pub header: DataHeader,
+    pub sensors: Vec<DataSensor>,
+}
+
+impl UbxEsfStatus {
+    pub fn new() -> Self {
+        Self {
+            name: "UBX-ESF-STATUS",
+            cid: UbxCID::new(CLS, ID),
+            ..Default::default()
+        }
+    }
+
+    pub fn fusion_mode_name(&self) -> &'static str {
+        match self.header.fusion_mode {
+            FUSION_MODE_INIT => "initializing",
+            FUSION_MODE_FUSION => "fusion",
+            FUSION_MODE_SUSPENDED => "suspended",
+            FUSION_MODE_DISABLED => "disabled",
+            _ => "unknown",
+        }
+    }
+
+    // Fusion filter is in initialization phase, e.g. after a reset
+    pub fn initializing(&self) -> bool {
+        self.header.fusion_mode == FUSION_MODE_INIT
+    }
+
+    // Fusion filter is combining GNSS and sensor data
+    pub fn fusion(&self) -> bool {
+        self.header.fusion_mode == FUSION_MODE_FUSION
+    }
+
+    // True if all sensors used by fusion filter are calibrated
+    pub fn calibrated(&self) -> bool {
+        let mut used = self.sensors.iter().filter(|s| s.used()).peekable();
+        used.peek().is_some() && used.all(|s| s.calibrated())
+    }
+
+    pub fn load(&mut self, data: &[u8]) {
+        // First read header to get number of sensor blocks that follow
+        let bytes = data.len();
+        assert!(bytes >= 16);
+        self.header = bincode::deserialize(&data[0..16]).unwrap();
+        assert!(bytes == 16 + 4 * self.header.num_sens as usize);
+
+        self.sensors.clear();
+        let mut offset = 16;
+        let size = 4;
+        while offset < bytes {
+            let sensor: DataSensor = bincode::deserialize(&data[offset..offset + size]).unwrap();
+            self.sensors.push(sensor);
+
+            offset += size;
+        }
+    }
+}
+
+impl UbxFrameInfo for UbxEsfStatus {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn cid(&self) -> UbxCID {
+        self.cid
+    }
+}
+
+impl UbxFrameDeSerialize for UbxEsfStatus {
+    fn from_bin(&mut self, data: &[u8]) {
+        self.load(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameSerialize;
+
+    #[test]
+    fn poll() {
+        let dut = UbxEsfStatusPoll::create();
+        assert_eq!(dut.name, "UBX-ESF-STATUS-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x10, 0x10, 0, 0, 32, 112]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 28] = [
+            0x10, 0x27, 0x00, 0x00, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 3, // header, 3 sensors
+            0xC5, 0x02, 100, 0x00, // gyro-z, used, ready, calibrated
+            0xCB, 0x01, 10, 0x00, // speed, used, ready, calibrating
+            0x90, 0x00, 100, 0x05, // accel-x, ready, not calibrated, faults
+        ];
+        let mut dut = UbxEsfStatus::new();
+        assert_eq!(dut.name, "UBX-ESF-STATUS");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.header.itow, 10000);
+        assert_eq!(dut.header.version, 2);
+        assert_eq!(dut.fusion_mode_name(), "fusion");
+        assert!(dut.fusion());
+        assert!(!dut.initializing());
+        assert_eq!(dut.sensors.len(), 3);
+
+        assert_eq!(dut.sensors[0].type_name(), "gyro-z");
+        assert!(dut.sensors[0].used());
+        assert!(dut.sensors[0].ready());
+        assert!(dut.sensors[0].calibrated());
+        assert_eq!(dut.sensors[0].freq, 100);
+
+        assert_eq!(dut.sensors[1].type_name(), "speed");
+        assert_eq!(dut.sensors[1].calib_status_name(), "calibrating");
+
+        assert_eq!(dut.sensors[2].type_name(), "accel-x");
+        assert!(!dut.sensors[2].used());
+        assert_eq!(dut.sensors[2].faults_to_string(), "bad-meas;missing-meas");
+
+        // Speed sensor not yet calibrated
+        assert!(!dut.calibrated());
+    }
+
+    #[test]
+    fn calibrated() {
+        const DATA: [u8; 24] = [
+            0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 2, // header, 2 sensors
+            0xC5, 0x03, 100, 0x00, // gyro-z, used, calibrated
+            0x90, 0x00, 100, 0x00, // accel-x, not used
+        ];
+        let mut dut = UbxEsfStatus::new();
+        dut.from_bin(&DATA);
+        assert!(dut.calibrated());
+    }
+
+    #[test]
+    fn no_sensors() {
+        const DATA: [u8; 16] = [0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut dut = UbxEsfStatus::new();
+        dut.from_bin(&DATA);
+        assert_eq!(dut.fusion_mode_name(), "initializing");
+        assert!(dut.initializing());
+        assert!(dut.sensors.is_empty());
+        assert!(!dut.calibrated());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 18] = [0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0];
+        let mut dut = UbxEsfStatus::new();
+        dut.from_bin(&DATA);
+    }
+}
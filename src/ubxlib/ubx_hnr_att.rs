@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x28;
+const ID: u8 = 0x01;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataHnrAtt {
+    pub itow: u32,
+    pub version: u8,
+    pub res1: [u8; 3],
+    pub roll: i32,        // 1e-5°
+    pub pitch: i32,       // 1e-5°
+    pub heading: i32,     // 1e-5°
+    pub acc_roll: u32,    // 1e-5°
+    pub acc_pitch: u32,   // 1e-5°
+    pub acc_heading: u32, // 1e-5°
+}
+
+pub struct UbxHnrAtt {}
+
+impl UbxHnrAtt {
+    pub fn create() -> UbxFrameWithData<DataHnrAtt> {
+        UbxFrameWithData::new("UBX-HNR-ATT", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn deserialize() {
+        let mut data = [0u8; 32];
+        data[0..4].copy_from_slice(&1000u32.to_le_bytes());
+        data[8..12].copy_from_slice(&(-150_000i32).to_le_bytes());
+        data[12..16].copy_from_slice(&250_000i32.to_le_bytes());
+        data[16..20].copy_from_slice(&18_000_000i32.to_le_bytes());
+        data[28..32].copy_from_slice(&70_000u32.to_le_bytes());
+
+        let mut dut = UbxHnrAtt::create();
+        assert_eq!(dut.name, "UBX-HNR-ATT");
+        dut.from_bin(&data);
+
+        assert_eq!(dut.data.itow, 1000);
+        assert_eq!(dut.data.roll, -150_000);
+        assert_eq!(dut.data.pitch, 250_000);
+        assert_eq!(dut.data.heading, 18_000_000);
+        assert_eq!(dut.data.acc_heading, 70_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_few_values() {
+        const DATA: [u8; 16] = [0; 16];
+        let mut dut = UbxHnrAtt::create();
+        dut.from_bin(&DATA);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x28;
+const ID: u8 = 0x00;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataHnrPvt {
+    pub itow: u32,
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub valid: u8, // bit 0: date, 1: time, 2: fully resolved
+    pub nano: i32,
+    pub gps_fix: u8,
+    pub flags: u8, // bit 0: gnss fix ok, 1: differential, 3: wheel ticks used, 4: heading valid
+    pub res1: [u8; 2],
+    pub lon: i32,      // 1e-7°
+    pub lat: i32,      // 1e-7°
+    pub height: i32,   // mm above ellipsoid
+    pub h_msl: i32,    // mm above mean sea level
+    pub g_speed: i32,  // ground speed mm/s
+    pub speed: i32,    // 3D speed mm/s
+    pub head_mot: i32, // 1e-5°
+    pub head_veh: i32, // 1e-5°
+    pub h_acc: u32,    // mm
+    pub v_acc: u32,    // mm
+    pub s_acc: u32,    // mm/s
+    pub head_acc: u32, // 1e-5°
+    pub res2: [u8; 4],
+}
+
+impl DataHnrPvt {
+    pub fn fix_ok(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+}
+
+pub struct UbxHnrPvt {}
+
+impl UbxHnrPvt {
+    pub fn create() -> UbxFrameWithData<DataHnrPvt> {
+        UbxFrameWithData::new("UBX-HNR-PVT", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn deserialize() {
+        let mut data = [0u8; 72];
+        data[0..4].copy_from_slice(&123_456u32.to_le_bytes());
+        data[4..6].copy_from_slice(&2020u16.to_le_bytes());
+        data[6] = 11;
+        data[7] = 30;
+        data[8] = 12;
+        data[16] = 3; // 3D fix
+        data[17] = 0x01;
+        data[20..24].copy_from_slice(&85_123_456i32.to_le_bytes());
+        data[24..28].copy_from_slice(&473_456_789i32.to_le_bytes());
+        data[36..40].copy_from_slice(&13_890i32.to_le_bytes());
+        data[44..48].copy_from_slice(&(-9_000_000i32).to_le_bytes());
+        data[64..68].copy_from_slice(&50_000u32.to_le_bytes());
+
+        let mut dut = UbxHnrPvt::create();
+        assert_eq!(dut.name, "UBX-HNR-PVT");
+        dut.from_bin(&data);
+
+        assert_eq!(dut.data.itow, 123_456);
+        assert_eq!(dut.data.year, 2020);
+        assert_eq!(dut.data.month, 11);
+        assert_eq!(dut.data.day, 30);
+        assert_eq!(dut.data.hour, 12);
+        assert_eq!(dut.data.gps_fix, 3);
+        assert!(dut.data.fix_ok());
+        assert_eq!(dut.data.lon, 85_123_456);
+        assert_eq!(dut.data.lat, 473_456_789);
+        assert_eq!(dut.data.g_speed, 13_890);
+        assert_eq!(dut.data.head_mot, -9_000_000);
+        assert_eq!(dut.data.head_acc, 50_000);
+    }
+}
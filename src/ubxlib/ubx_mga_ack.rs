@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+pub const CLS_MGA: u8 = 0x13;
+const ID: u8 = 0x60;
+
+// Requires ackAiding in UBX-CFG-NAVX5
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataMgaAck {
+    pub ack_type: u8, // 0: not used, 1: accepted
+    pub version: u8,
+    pub info_code: u8,
+    pub msg_id: u8, // id of acknowledged MGA message
+    pub msg_payload_start: [u8; 4],
+}
+
+impl DataMgaAck {
+    pub fn accepted(&self) -> bool {
+        self.ack_type == 1
+    }
+
+    pub fn info_name(&self) -> &'static str {
+        match self.info_code {
+            0 => "accepted",
+            1 => "no time",
+            2 => "version not supported",
+            3 => "size mismatch",
+            4 => "database store failed",
+            5 => "receiver not ready",
+            6 => "type unknown",
+            _ => "unknown",
+        }
+    }
+}
+
+pub struct UbxMgaAck {}
+
+impl UbxMgaAck {
+    pub fn create() -> UbxFrameWithData<DataMgaAck> {
+        UbxFrameWithData::new("UBX-MGA-ACK", UbxCID::new(CLS_MGA, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn accepted() {
+        const DATA: [u8; 8] = [1, 0, 0, 0x40, 0x10, 0, 0x12, 0];
+        let mut dut = UbxMgaAck::create();
+        assert_eq!(dut.name, "UBX-MGA-ACK");
+        dut.from_bin(&DATA);
+
+        assert!(dut.data.accepted());
+        assert_eq!(dut.data.msg_id, 0x40);
+        assert_eq!(dut.data.info_name(), "accepted");
+    }
+
+    #[test]
+    fn rejected() {
+        const DATA: [u8; 8] = [0, 0, 5, 0x40, 0x10, 0, 0x12, 0];
+        let mut dut = UbxMgaAck::create();
+        dut.from_bin(&DATA);
+
+        assert!(!dut.data.accepted());
+        assert_eq!(dut.data.info_name(), "receiver not ready");
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 4] = [1, 0, 0, 0x40];
+        let mut dut = UbxMgaAck::create();
+        dut.from_bin(&DATA);
+    }
+}
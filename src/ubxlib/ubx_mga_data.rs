@@ -0,0 +1,132 @@
+/*
+ * Generic assistance (MGA) message, e.g. read from an AssistNow file
+ *
+ * AssistNow Online/Offline files are plain concatenations of UBX-MGA
+ * frames (ANO, EPH, ALM, IONO, UTC, ...) that are sent to the receiver
+ * unmodified.
+ */
+
+use crate::ubxlib::checksum::Checksum;
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::error::Error;
+use crate::ubxlib::frame::{UbxFrame, UbxFrameInfo, UbxFrameSerialize};
+use crate::ubxlib::ubx_mga_ack::CLS_MGA;
+
+#[derive(Debug)]
+pub struct UbxMgaData {
+    pub name: &'static str,
+    pub cid: UbxCID,
+    pub data: Vec<u8>,
+}
+
+impl UbxMgaData {
+    pub fn new(id: u8, data: &[u8]) -> Self {
+        Self {
+            name: "UBX-MGA",
+            cid: UbxCID::new(CLS_MGA, id),
+            data: data.to_vec(),
+        }
+    }
+
+    // Splits file content into MGA messages, fails on any invalid or non MGA frame
+    pub fn split(bytes: &[u8]) -> Result<Vec<UbxMgaData>, Error> {
+        let mut messages = Vec::new();
+        let mut ofs = 0;
+
+        while ofs < bytes.len() {
+            let rest = &bytes[ofs..];
+            if rest.len() < 8 || rest[0] != 0xb5 || rest[1] != 0x62 {
+                return Err(Error::InvalidFrame(ofs));
+            }
+
+            let len = rest[4] as usize + rest[5] as usize * 256;
+            if rest.len() < 8 + len || rest[2] != CLS_MGA {
+                return Err(Error::InvalidFrame(ofs));
+            }
+
+            let mut checksum = Checksum::new();
+            for byte in &rest[2..6 + len] {
+                checksum.add(*byte);
+            }
+            if !checksum.matches(rest[6 + len], rest[7 + len]) {
+                return Err(Error::InvalidFrame(ofs));
+            }
+
+            messages.push(UbxMgaData::new(rest[3], &rest[6..6 + len]));
+            ofs += 8 + len;
+        }
+
+        Ok(messages)
+    }
+}
+
+impl UbxFrameInfo for UbxMgaData {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn cid(&self) -> UbxCID {
+        self.cid
+    }
+}
+
+impl UbxFrameSerialize for UbxMgaData {
+    fn to_bin(&self) -> Vec<u8> {
+        UbxFrame::bytes(self.cid(), &self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MGA_FILE: &[u8] = include_bytes!("../../test_files/mga_offline.ubx");
+
+    #[test]
+    fn split() {
+        let messages = UbxMgaData::split(MGA_FILE).unwrap();
+        assert_eq!(messages.len(), 3);
+
+        // INI-TIME_UTC, GPS-IONO, GPS-UTC
+        assert_eq!(messages[0].cid, UbxCID::new(0x13, 0x40));
+        assert_eq!(messages[0].data.len(), 24);
+        assert_eq!(messages[1].cid, UbxCID::new(0x13, 0x00));
+        assert_eq!(messages[1].data[0], 0x06);
+        assert_eq!(messages[2].data[0], 0x05);
+        assert_eq!(messages[2].data.len(), 20);
+    }
+
+    #[test]
+    fn serialize() {
+        let messages = UbxMgaData::split(MGA_FILE).unwrap();
+        let bytes: Vec<u8> = messages.iter().flat_map(|m| m.to_bin()).collect();
+        assert_eq!(bytes, MGA_FILE);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(UbxMgaData::split(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        // Truncated last frame
+        let res = UbxMgaData::split(&MGA_FILE[..MGA_FILE.len() - 1]);
+        assert!(matches!(res, Err(Error::InvalidFrame(56))));
+
+        // Checksum error in first frame
+        let mut data = MGA_FILE.to_vec();
+        data[10] ^= 0xff;
+        assert!(matches!(
+            UbxMgaData::split(&data),
+            Err(Error::InvalidFrame(0))
+        ));
+
+        // Not an MGA message (UBX-ACK-ACK)
+        let data = [0xb5, 0x62, 0x05, 0x01, 0x02, 0x00, 1, 2, 11, 47];
+        assert!(matches!(
+            UbxMgaData::split(&data),
+            Err(Error::InvalidFrame(0))
+        ));
+    }
+}
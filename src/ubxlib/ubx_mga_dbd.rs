@@ -0,0 +1,29 @@
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFramePoll;
+
+const CLS: u8 = 0x13;
+const ID: u8 = 0x80;
+
+// Receiver answers with a sequence of UBX-MGA-DBD messages, the navigation database.
+// The messages are sent back unmodified to restore the database, see UbxMgaData.
+pub struct UbxMgaDbdPoll {}
+
+impl UbxMgaDbdPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-MGA-DBD-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameSerialize;
+
+    #[test]
+    fn poll() {
+        let dut = UbxMgaDbdPoll::create();
+        assert_eq!(dut.name, "UBX-MGA-DBD-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x13, 0x80, 0, 0, 147, 204]);
+    }
+}
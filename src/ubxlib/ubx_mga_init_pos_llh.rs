@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x13;
+const ID: u8 = 0x40;
+
+#[derive(Default, Debug, Serialize)]
+pub struct DataMgaIniPosLlh {
+    pub msg_type: u8, // Name type is a keyword that can't be used in Rust
+    pub msg_version: u8,
+    pub res1: [u8; 2],
+    pub lat: i32,     // 1e-7°
+    pub lon: i32,     // 1e-7°
+    pub alt: i32,     // cm above ellipsoid
+    pub pos_acc: u32, // cm
+}
+
+impl DataMgaIniPosLlh {
+    pub fn new() -> Self {
+        Self {
+            msg_type: 0x01, // 0x01 for LLH position format
+            ..Default::default()
+        }
+    }
+}
+
+pub struct UbxMgaIniPosLlh {}
+
+impl UbxMgaIniPosLlh {
+    pub fn create() -> UbxFrameWithData<DataMgaIniPosLlh> {
+        UbxFrameWithData::init(
+            "UBX-MGA-INI-POS_LLH",
+            UbxCID::new(CLS, ID),
+            DataMgaIniPosLlh::new(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameInfo, UbxFrameSerialize};
+
+    #[test]
+    fn set() {
+        let mut dut = UbxMgaIniPosLlh::create();
+        assert_eq!(dut.name(), "UBX-MGA-INI-POS_LLH");
+        dut.data.lat = 473_456_789;
+        dut.data.lon = 85_123_456;
+        dut.data.alt = 48_050;
+        dut.data.pos_acc = 250;
+
+        let msg = dut.to_bin();
+        assert_eq!(msg[0..10], [0xb5, 0x62, 0x13, 0x40, 20, 0, 0x01, 0, 0, 0]);
+        assert_eq!(msg[10..14], 473_456_789i32.to_le_bytes());
+        assert_eq!(msg[14..18], 85_123_456i32.to_le_bytes());
+        assert_eq!(msg[18..22], 48_050i32.to_le_bytes());
+        assert_eq!(msg[22..26], 250u32.to_le_bytes());
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use chrono::prelude::*;
 use serde::Serialize;
 
@@ -7,6 +9,14 @@ use crate::ubxlib::frame::UbxFrameWithData;
 const CLS: u8 = 0x13;
 const ID: u8 = 0x40;
 
+// Reference point of the given time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeRef {
+    Receipt = 0, // on receipt of message, inaccurate
+    ExtInt0 = 1, // next pulse on EXTINT0
+    ExtInt1 = 2, // next pulse on EXTINT1
+}
+
 #[derive(Default, Debug, Serialize)]
 pub struct DataMgaIniTimeUtc {
     pub msg_type: u8, // Name type is a keyword that can't be used in Rust
@@ -49,12 +59,18 @@ impl UbxMgaIniTimeUtc {
 }
 
 impl UbxFrameWithData<DataMgaIniTimeUtc> {
-    pub fn set_date_time(&mut self, utc: &DateTime<Utc>) {
+    pub fn set_date_time(
+        &mut self,
+        utc: &DateTime<Utc>,
+        accuracy: Duration,
+        leap_secs: Option<i8>,
+        time_ref: TimeRef,
+    ) {
         self.data.msg_type = 0x10; // 0x10 for UTC time format
         self.data.msg_version = 0x00;
-        self.data.msg_ref = 0x00; // receipt of message will be inaccurate
+        self.data.msg_ref = time_ref as u8;
 
-        self.data.leap_secs = -128; // number of leap seconds is unknown
+        self.data.leap_secs = leap_secs.unwrap_or(-128); // -128: number of leap seconds is unknown
 
         self.data.year = utc.year() as u16;
         self.data.month = utc.month() as u8;
@@ -62,12 +78,11 @@ impl UbxFrameWithData<DataMgaIniTimeUtc> {
         self.data.hour = utc.hour() as u8;
         self.data.minute = utc.minute() as u8;
         self.data.second = utc.second() as u8;
-        self.data.ns = 0; // dt.microsecond * 1000.0
+        // chrono reports leap second as nanoseconds >= 1e9
+        self.data.ns = utc.nanosecond().min(999_999_999);
 
-        self.data.tacc_s = 10;
-        self.data.tacc_ns = 0; // 999999999
-                               // Accuracy 0 taken from u-center example
-                               // not sure whether this is correct
+        self.data.tacc_s = accuracy.as_secs().min(u16::MAX as u64) as u16;
+        self.data.tacc_ns = accuracy.subsec_nanos();
     }
 }
 
@@ -91,7 +106,7 @@ mod tests {
         let mut dut = UbxMgaIniTimeUtc::create();
 
         let utc = Utc.ymd(2020, 2, 3).and_hms_milli(11, 22, 33, 444);
-        dut.set_date_time(&utc);
+        dut.set_date_time(&utc, Duration::from_secs(10), None, TimeRef::Receipt);
 
         assert_eq!(dut.data.year, 2020);
         assert_eq!(dut.data.month, 2);
@@ -105,8 +120,23 @@ mod tests {
             msg[0..30],
             [
                 0xb5, 0x62, 0x13, 0x40, 24, 0, 0x10, 0x00, 0x00, 128, 228, 7, 2, 3, 11, 22, 33, 0,
-                0, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0
+                0x00, 0xe7, 0x76, 0x1a, 10, 0, 0, 0, 0, 0, 0, 0
             ]
         );
     }
+
+    #[test]
+    fn set_date_time_extint() {
+        let mut dut = UbxMgaIniTimeUtc::create();
+
+        let utc = Utc.ymd(2020, 2, 3).and_hms(11, 22, 34);
+        let accuracy = Duration::from_micros(1500);
+        dut.set_date_time(&utc, accuracy, Some(18), TimeRef::ExtInt0);
+
+        assert_eq!(dut.data.msg_ref, 1);
+        assert_eq!(dut.data.leap_secs, 18);
+        assert_eq!(dut.data.ns, 0);
+        assert_eq!(dut.data.tacc_s, 0);
+        assert_eq!(dut.data.tacc_ns, 1_500_000);
+    }
 }
@@ -69,7 +69,7 @@ impl UbxMonVer {
         self.frame.data.hw_version = UbxMonVer::extract_string(&data[30..40]);
 
         if bytes > 40 {
-            assert!((bytes - 40) % 30 == 0);
+            assert!((bytes - 40).is_multiple_of(30));
 
             let mut offset = 40;
             let size = 30;
@@ -84,7 +84,7 @@ impl UbxMonVer {
 
     fn extract_string(data: &[u8]) -> String {
         // Version strings are zero padded, remove these and return String
-        String::from_utf8_lossy(&data).replace(|c: char| c == '\0', "")
+        String::from_utf8_lossy(data).replace('\0', "")
     }
 }
 
@@ -100,7 +100,7 @@ impl UbxFrameInfo for UbxMonVer {
 
 impl UbxFrameDeSerialize for UbxMonVer {
     fn from_bin(&mut self, data: &[u8]) {
-        self.load(&data);
+        self.load(data);
     }
 }
 
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x01;
+const ID: u8 = 0x05;
+
+pub struct UbxNavAttPoll {}
+
+impl UbxNavAttPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-NAV-ATT-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataNavAtt {
+    pub itow: u32,
+    pub version: u8,
+    pub res1: [u8; 3],
+    pub roll: i32,        // 1e-5°
+    pub pitch: i32,       // 1e-5°
+    pub heading: i32,     // 1e-5°
+    pub acc_roll: u32,    // 1e-5°
+    pub acc_pitch: u32,   // 1e-5°
+    pub acc_heading: u32, // 1e-5°
+}
+
+pub struct UbxNavAtt {}
+
+impl UbxNavAtt {
+    pub fn create() -> UbxFrameWithData<DataNavAtt> {
+        UbxFrameWithData::new("UBX-NAV-ATT", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxNavAttPoll::create();
+        assert_eq!(dut.name, "UBX-NAV-ATT-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x01, 0x05, 0, 0, 6, 19]);
+    }
+
+    #[test]
+    fn deserialize() {
+        let mut data = [0u8; 32];
+        data[0..4].copy_from_slice(&2000u32.to_le_bytes());
+        data[8..12].copy_from_slice(&(-320_000i32).to_le_bytes());
+        data[12..16].copy_from_slice(&110_000i32.to_le_bytes());
+        data[16..20].copy_from_slice(&27_012_345i32.to_le_bytes());
+        data[20..24].copy_from_slice(&50_000u32.to_le_bytes());
+        data[24..28].copy_from_slice(&60_000u32.to_le_bytes());
+        data[28..32].copy_from_slice(&90_000u32.to_le_bytes());
+
+        let mut dut = UbxNavAtt::create();
+        assert_eq!(dut.name, "UBX-NAV-ATT");
+        dut.from_bin(&data);
+
+        assert_eq!(dut.data.itow, 2000);
+        assert_eq!(dut.data.roll, -320_000);
+        assert_eq!(dut.data.pitch, 110_000);
+        assert_eq!(dut.data.heading, 27_012_345);
+        assert_eq!(dut.data.acc_roll, 50_000);
+        assert_eq!(dut.data.acc_pitch, 60_000);
+        assert_eq!(dut.data.acc_heading, 90_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 20] = [0; 20];
+        let mut dut = UbxNavAtt::create();
+        dut.from_bin(&DATA);
+    }
+}
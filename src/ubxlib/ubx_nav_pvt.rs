@@ -0,0 +1,176 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x01;
+const ID: u8 = 0x07;
+
+pub struct UbxNavPvtPoll {}
+
+impl UbxNavPvtPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-NAV-PVT-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataNavPvt {
+    pub itow: u32,
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub valid: u8,  // bit 0: date, 1: time, 2: fully resolved, 3: magnetic declination
+    pub t_acc: u32, // ns
+    pub nano: i32,
+    pub fix_type: u8, // 0: no fix, 1: DR only, 2: 2D, 3: 3D, 4: GNSS + DR, 5: time only
+    pub flags: u8,    // bit 0: gnss fix ok, 1: differential, 5: heading valid
+    pub flags2: u8,   // bit 5: confirmed available, 6: date confirmed, 7: time confirmed
+    pub num_sv: u8,
+    pub lon: i32,      // 1e-7°
+    pub lat: i32,      // 1e-7°
+    pub height: i32,   // mm above ellipsoid
+    pub h_msl: i32,    // mm above mean sea level
+    pub h_acc: u32,    // mm
+    pub v_acc: u32,    // mm
+    pub vel_n: i32,    // mm/s
+    pub vel_e: i32,    // mm/s
+    pub vel_d: i32,    // mm/s
+    pub g_speed: i32,  // ground speed mm/s
+    pub head_mot: i32, // 1e-5°
+    pub s_acc: u32,    // mm/s
+    pub head_acc: u32, // 1e-5°
+    pub p_dop: u16,    // 0.01
+    pub flags3: u8,    // bit 0: invalid lon, lat, height and hMSL
+    pub res1: [u8; 5],
+    pub head_veh: i32, // 1e-5°
+    pub mag_dec: i16,  // 1e-2°
+    pub mag_acc: u16,  // 1e-2°
+}
+
+impl DataNavPvt {
+    pub fn fix_ok(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    // Date and time valid and fully resolved
+    pub fn time_resolved(&self) -> bool {
+        self.valid & 0x07 == 0x07
+    }
+
+    // UTC date and time, if both are valid
+    pub fn utc(&self) -> Option<DateTime<Utc>> {
+        if self.valid & 0x03 != 0x03 {
+            return None;
+        }
+        let date = NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)?;
+        let time = date.and_hms_opt(self.hour as u32, self.min as u32, self.sec as u32)?;
+        let time = time + chrono::Duration::nanoseconds(self.nano as i64);
+        Some(DateTime::from_utc(time, Utc))
+    }
+
+    // Position is usable if fix is valid and includes GNSS measurements
+    pub fn position_valid(&self) -> bool {
+        self.fix_ok() && (2..=4).contains(&self.fix_type) && self.flags3 & 0x01 == 0
+    }
+}
+
+pub struct UbxNavPvt {}
+
+impl UbxNavPvt {
+    pub fn create() -> UbxFrameWithData<DataNavPvt> {
+        UbxFrameWithData::new("UBX-NAV-PVT", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxNavPvtPoll::create();
+        assert_eq!(dut.name, "UBX-NAV-PVT-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x01, 0x07, 0, 0, 8, 25]);
+    }
+
+    #[test]
+    fn deserialize() {
+        let mut data = [0u8; 92];
+        data[0..4].copy_from_slice(&123_456u32.to_le_bytes());
+        data[4..6].copy_from_slice(&2020u16.to_le_bytes());
+        data[6] = 11;
+        data[7] = 4;
+        data[11] = 0x07;
+        data[20] = 3; // 3D fix
+        data[21] = 0x01;
+        data[23] = 12;
+        data[24..28].copy_from_slice(&85_123_456i32.to_le_bytes());
+        data[28..32].copy_from_slice(&473_456_789i32.to_le_bytes());
+        data[32..36].copy_from_slice(&480_500i32.to_le_bytes());
+        data[40..44].copy_from_slice(&2_500u32.to_le_bytes());
+        data[84..88].copy_from_slice(&9_012_000i32.to_le_bytes());
+
+        let mut dut = UbxNavPvt::create();
+        assert_eq!(dut.name, "UBX-NAV-PVT");
+        dut.from_bin(&data);
+
+        assert_eq!(dut.data.itow, 123_456);
+        assert_eq!(dut.data.year, 2020);
+        assert_eq!(dut.data.month, 11);
+        assert_eq!(dut.data.day, 4);
+        assert_eq!(dut.data.num_sv, 12);
+        assert_eq!(dut.data.lon, 85_123_456);
+        assert_eq!(dut.data.lat, 473_456_789);
+        assert_eq!(dut.data.height, 480_500);
+        assert_eq!(dut.data.h_acc, 2_500);
+        assert_eq!(dut.data.head_veh, 9_012_000);
+        assert!(dut.data.position_valid());
+    }
+
+    #[test]
+    fn utc() {
+        let mut dut = UbxNavPvt::create();
+        dut.data.year = 2020;
+        dut.data.month = 11;
+        dut.data.day = 4;
+        dut.data.hour = 10;
+        dut.data.min = 20;
+        dut.data.sec = 30;
+        dut.data.nano = -1000;
+        assert_eq!(dut.data.utc(), None);
+
+        dut.data.valid = 0x03;
+        let utc = Utc.ymd(2020, 11, 4).and_hms_nano(10, 20, 29, 999_999_000);
+        assert_eq!(dut.data.utc(), Some(utc));
+    }
+
+    #[test]
+    fn position_invalid() {
+        let mut data = [0u8; 92];
+        data[20] = 5; // time only fix
+        data[21] = 0x01;
+
+        let mut dut = UbxNavPvt::create();
+        dut.from_bin(&data);
+        assert!(!dut.data.position_valid());
+
+        dut.data.fix_type = 3;
+        dut.data.flags3 = 0x01;
+        assert!(!dut.data.position_valid());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 84] = [0; 84];
+        let mut dut = UbxNavPvt::create();
+        dut.from_bin(&DATA);
+    }
+}
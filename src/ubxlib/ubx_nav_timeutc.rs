@@ -0,0 +1,99 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+
+const CLS: u8 = 0x01;
+const ID: u8 = 0x21;
+
+// valid bits
+pub const VALID_TOW: u8 = 0x01;
+pub const VALID_WKN: u8 = 0x02;
+pub const VALID_UTC: u8 = 0x04;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataNavTimeUtc {
+    pub itow: u32,
+    pub t_acc: u32, // ns
+    pub nano: i32,
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub valid: u8, // bit 0: time of week, 1: week number, 2: UTC, bits 4..7: UTC standard
+}
+
+impl DataNavTimeUtc {
+    // Time of week and week number known, leap seconds applied
+    pub fn fully_resolved(&self) -> bool {
+        let mask = VALID_TOW | VALID_WKN | VALID_UTC;
+        self.valid & mask == mask
+    }
+
+    // UTC date and time, if fully resolved
+    pub fn utc(&self) -> Option<DateTime<Utc>> {
+        if !self.fully_resolved() {
+            return None;
+        }
+        let date = NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)?;
+        let time = date.and_hms_opt(self.hour as u32, self.min as u32, self.sec as u32)?;
+        let time = time + chrono::Duration::nanoseconds(self.nano as i64);
+        Some(DateTime::from_utc(time, Utc))
+    }
+}
+
+pub struct UbxNavTimeUtc {}
+
+impl UbxNavTimeUtc {
+    pub fn create() -> UbxFrameWithData<DataNavTimeUtc> {
+        UbxFrameWithData::new("UBX-NAV-TIMEUTC", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 20] = [
+            0x40, 0xe2, 0x01, 0x00, // itow 123456
+            0x19, 0x00, 0x00, 0x00, // t_acc 25 ns
+            0x00, 0xe7, 0x76, 0x1a, // nano 444 ms
+            0xe4, 0x07, 11, 4, 13, 37, 42,   // 2020-11-04 13:37:42
+            0x27, // valid, UTC standard 2 (USNO)
+        ];
+        let mut dut = UbxNavTimeUtc::create();
+        assert_eq!(dut.name, "UBX-NAV-TIMEUTC");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.itow, 123_456);
+        assert_eq!(dut.data.t_acc, 25);
+        assert!(dut.data.fully_resolved());
+        let utc = dut.data.utc().unwrap();
+        assert_eq!(utc.to_rfc3339(), "2020-11-04T13:37:42.444+00:00");
+    }
+
+    #[test]
+    fn not_resolved() {
+        let mut dut = UbxNavTimeUtc::create();
+        dut.data.year = 2020;
+        dut.data.month = 11;
+        dut.data.day = 4;
+        dut.data.valid = VALID_TOW | VALID_UTC;
+        assert!(!dut.data.fully_resolved());
+        assert!(dut.data.utc().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 19] = [0; 19];
+        let mut dut = UbxNavTimeUtc::create();
+        dut.from_bin(&DATA);
+    }
+}
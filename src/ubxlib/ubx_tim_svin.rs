@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
+
+const CLS: u8 = 0x0D;
+const ID: u8 = 0x04;
+
+pub struct UbxTimSvinPoll {}
+
+impl UbxTimSvinPoll {
+    pub fn create() -> UbxFramePoll {
+        UbxFramePoll::new("UBX-TIM-SVIN-POLL", UbxCID::new(CLS, ID))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataTimSvin {
+    pub dur: u32,    // s, survey-in duration so far
+    pub mean_x: i32, // cm, ECEF
+    pub mean_y: i32, // cm, ECEF
+    pub mean_z: i32, // cm, ECEF
+    pub mean_v: u32, // mm², variance of position
+    pub obs: u32,    // number of position observations
+    pub valid: u8,   // 1: survey-in position valid
+    pub active: u8,  // 1: survey-in in progress
+    pub res1: [u8; 2],
+}
+
+impl DataTimSvin {
+    // Accuracy of mean position in m
+    pub fn accuracy(&self) -> f64 {
+        (self.mean_v as f64).sqrt() / 1000.0
+    }
+
+    pub fn completed(&self) -> bool {
+        self.valid == 1 && self.active == 0
+    }
+}
+
+pub struct UbxTimSvin {}
+
+impl UbxTimSvin {
+    pub fn create() -> UbxFrameWithData<DataTimSvin> {
+        UbxFrameWithData::new("UBX-TIM-SVIN", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::{UbxFrameDeSerialize, UbxFrameSerialize};
+
+    #[test]
+    fn poll() {
+        let dut = UbxTimSvinPoll::create();
+        assert_eq!(dut.name, "UBX-TIM-SVIN-POLL");
+        let msg = dut.to_bin();
+        assert_eq!(msg, [0xb5, 0x62, 0x0d, 0x04, 0, 0, 17, 64]);
+    }
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 28] = [
+            0x2c, 0x01, 0, 0, // 300 s
+            0x5c, 0x59, 0x9b, 0x18, // x 4128341.40 m
+            0xfd, 0x8f, 0xb4, 0x04, // y 789422.05 m
+            0x8f, 0x52, 0x6a, 0x1b, // z 4599527.83 m
+            0x40, 0x42, 0x0f, 0x00, // variance 1 m²
+            0x2c, 0x01, 0, 0, // 300 observations
+            1, 0, 0, 0, // valid, not active
+        ];
+        let mut dut = UbxTimSvin::create();
+        assert_eq!(dut.name, "UBX-TIM-SVIN");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.dur, 300);
+        assert_eq!(dut.data.mean_x, 412_834_140);
+        assert_eq!(dut.data.obs, 300);
+        assert!((dut.data.accuracy() - 1.0).abs() < 1e-9);
+        assert!(dut.data.completed());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 27] = [0; 27];
+        let mut dut = UbxTimSvin::create();
+        dut.from_bin(&DATA);
+    }
+}
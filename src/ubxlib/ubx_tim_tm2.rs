@@ -0,0 +1,169 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+use crate::ubxlib::gps_time;
+
+const CLS: u8 = 0x0D;
+const ID: u8 = 0x03;
+
+// flags bits
+pub const FLAG_NEW_FALLING_EDGE: u8 = 0x04;
+pub const FLAG_UTC_AVAILABLE: u8 = 0x20;
+pub const FLAG_TIME_VALID: u8 = 0x40;
+pub const FLAG_NEW_RISING_EDGE: u8 = 0x80;
+const TIME_BASE_SHIFT: u8 = 3;
+const TIME_BASE_MASK: u8 = 0x18;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeBase {
+    Receiver = 0,
+    Gnss = 1,
+    Utc = 2,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataTimTm2 {
+    pub ch: u8,     // EXTINT channel
+    pub flags: u8,  // bits 3..4: time base, others see FLAG_*
+    pub count: u16, // rising edge counter
+    pub wn_r: u16,
+    pub wn_f: u16,
+    pub tow_ms_r: u32,
+    pub tow_sub_ms_r: u32, // ns
+    pub tow_ms_f: u32,
+    pub tow_sub_ms_f: u32, // ns
+    pub acc_est: u32,      // ns
+}
+
+impl DataTimTm2 {
+    pub fn flag(&self, mask: u8) -> bool {
+        self.flags & mask != 0
+    }
+
+    pub fn time_base(&self) -> Option<TimeBase> {
+        match (self.flags & TIME_BASE_MASK) >> TIME_BASE_SHIFT {
+            0 => Some(TimeBase::Receiver),
+            1 => Some(TimeBase::Gnss),
+            2 => Some(TimeBase::Utc),
+            _ => None,
+        }
+    }
+
+    // UTC time of last rising edge, see edge_utc
+    pub fn rising_utc(&self, leap_secs: Option<i64>) -> Option<DateTime<Utc>> {
+        self.edge_utc(self.wn_r, self.tow_ms_r, self.tow_sub_ms_r, leap_secs)
+    }
+
+    // UTC time of last falling edge, see edge_utc
+    pub fn falling_utc(&self, leap_secs: Option<i64>) -> Option<DateTime<Utc>> {
+        self.edge_utc(self.wn_f, self.tow_ms_f, self.tow_sub_ms_f, leap_secs)
+    }
+
+    /*
+    Edge time in UTC
+
+    Time of week is in UTC if the time pulse is aligned to UTC, otherwise it
+    is in GNSS time and the leap seconds must be known. GNSS time is assumed
+    to be GPS time. Marks in receiver local time can't be mapped.
+    */
+    fn edge_utc(
+        &self,
+        week: u16,
+        tow_ms: u32,
+        sub_ns: u32,
+        leap_secs: Option<i64>,
+    ) -> Option<DateTime<Utc>> {
+        if !self.flag(FLAG_TIME_VALID) {
+            return None;
+        }
+        let time = gps_time::from_week_tow(week, tow_ms, sub_ns);
+        match self.time_base()? {
+            TimeBase::Utc if self.flag(FLAG_UTC_AVAILABLE) => Some(time),
+            TimeBase::Gnss => Some(time - Duration::seconds(leap_secs?)),
+            _ => None,
+        }
+    }
+}
+
+pub struct UbxTimTm2 {}
+
+impl UbxTimTm2 {
+    pub fn create() -> UbxFrameWithData<DataTimTm2> {
+        UbxFrameWithData::new("UBX-TIM-TM2", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 28] = [
+            0x00, 0xed, // EXTINT0, running, new edges, GNSS time base, UTC and time valid
+            0x03, 0x00, // count 3
+            0x52, 0x08, 0x52, 0x08, // week 2130
+            0xf0, 0x03, 0x72, 0x12, // rising tow 309462000 ms
+            0x20, 0xa1, 0x07, 0x00, // 500000 ns
+            0x54, 0x04, 0x72, 0x12, // falling tow 309462100 ms
+            0x00, 0x00, 0x00, 0x00, // 0 ns
+            0x19, 0x00, 0x00, 0x00, // accuracy 25 ns
+        ];
+        let mut dut = UbxTimTm2::create();
+        assert_eq!(dut.name, "UBX-TIM-TM2");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.count, 3);
+        assert_eq!(dut.data.wn_f, 2130);
+        assert_eq!(dut.data.tow_sub_ms_r, 500_000);
+        assert_eq!(dut.data.acc_est, 25);
+        assert!(dut.data.flag(FLAG_NEW_RISING_EDGE));
+        assert!(dut.data.flag(FLAG_NEW_FALLING_EDGE));
+        assert_eq!(dut.data.time_base(), Some(TimeBase::Gnss));
+
+        assert!(dut.data.rising_utc(None).is_none());
+        assert_eq!(
+            dut.data.rising_utc(Some(18)).unwrap(),
+            Utc.ymd(2020, 11, 4).and_hms_micro(13, 57, 24, 500)
+        );
+        assert_eq!(
+            dut.data.falling_utc(Some(18)).unwrap(),
+            Utc.ymd(2020, 11, 4).and_hms_milli(13, 57, 24, 100)
+        );
+    }
+
+    #[test]
+    fn time_base() {
+        let mut dut = UbxTimTm2::create();
+        dut.data.wn_r = 2130;
+        dut.data.tow_ms_r = 309_444_000;
+        dut.data.flags = FLAG_TIME_VALID | FLAG_UTC_AVAILABLE | (2 << TIME_BASE_SHIFT);
+        assert_eq!(
+            dut.data.rising_utc(None).unwrap(),
+            Utc.ymd(2020, 11, 4).and_hms(13, 57, 24)
+        );
+
+        // UTC not yet available
+        dut.data.flags = FLAG_TIME_VALID | (2 << TIME_BASE_SHIFT);
+        assert!(dut.data.rising_utc(Some(18)).is_none());
+
+        dut.data.flags = FLAG_TIME_VALID | FLAG_UTC_AVAILABLE;
+        assert_eq!(dut.data.time_base(), Some(TimeBase::Receiver));
+        assert!(dut.data.rising_utc(Some(18)).is_none());
+
+        dut.data.flags = FLAG_UTC_AVAILABLE | (1 << TIME_BASE_SHIFT);
+        assert!(dut.data.rising_utc(Some(18)).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 27] = [0; 27];
+        let mut dut = UbxTimTm2::create();
+        dut.from_bin(&DATA);
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::ubxlib::cid::UbxCID;
+use crate::ubxlib::frame::UbxFrameWithData;
+use crate::ubxlib::gps_time;
+
+const CLS: u8 = 0x0D;
+const ID: u8 = 0x01;
+
+// flags bits
+pub const FLAG_TIME_BASE_UTC: u8 = 0x01;
+pub const FLAG_UTC_AVAILABLE: u8 = 0x02;
+pub const FLAG_QERR_INVALID: u8 = 0x10;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct DataTimTp {
+    pub tow_ms: u32,     // time of week of next pulse
+    pub tow_sub_ms: u32, // 2^-32 ms
+    pub q_err: i32,      // ps, quantization error of next pulse
+    pub week: u16,
+    pub flags: u8,    // bit 0: UTC time base, 1: UTC available, 4: qErr invalid
+    pub ref_info: u8, // bits 0..3: GNSS time reference (0: GPS), 4..7: UTC standard
+}
+
+impl DataTimTp {
+    // Quantization error of next pulse in ps, if known
+    pub fn q_err(&self) -> Option<i32> {
+        if self.flags & FLAG_QERR_INVALID != 0 {
+            return None;
+        }
+        Some(self.q_err)
+    }
+
+    /*
+    UTC time of next pulse
+
+    Time of week is in UTC if the pulse is aligned to UTC, otherwise it is in
+    GNSS time and the leap seconds must be known. Only GPS time is supported
+    as GNSS time reference.
+    */
+    pub fn pulse_utc(&self, leap_secs: Option<i64>) -> Option<DateTime<Utc>> {
+        let sub_ns = ((self.tow_sub_ms as u64 * 1_000_000) >> 32) as u32;
+        let time = gps_time::from_week_tow(self.week, self.tow_ms, sub_ns);
+        if self.flags & FLAG_TIME_BASE_UTC != 0 {
+            if self.flags & FLAG_UTC_AVAILABLE == 0 {
+                return None;
+            }
+            Some(time)
+        } else if self.ref_info & 0x0F == 0 {
+            Some(time - Duration::seconds(leap_secs?))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct UbxTimTp {}
+
+impl UbxTimTp {
+    pub fn create() -> UbxFrameWithData<DataTimTp> {
+        UbxFrameWithData::new("UBX-TIM-TP", UbxCID::new(CLS, ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubxlib::frame::UbxFrameDeSerialize;
+
+    #[test]
+    fn deserialize() {
+        const DATA: [u8; 16] = [
+            0xf0, 0x03, 0x72, 0x12, // tow 309462000 ms
+            0x00, 0x00, 0x00, 0x80, // 0.5 ms
+            0x9c, 0xff, 0xff, 0xff, // qErr -100 ps
+            0x52, 0x08, // week 2130
+            0x02, 0x20, // GPS time base, UTC available, USNO
+        ];
+        let mut dut = UbxTimTp::create();
+        assert_eq!(dut.name, "UBX-TIM-TP");
+        dut.from_bin(&DATA);
+
+        assert_eq!(dut.data.tow_ms, 309_462_000);
+        assert_eq!(dut.data.q_err(), Some(-100));
+        assert_eq!(dut.data.week, 2130);
+
+        assert!(dut.data.pulse_utc(None).is_none());
+        assert_eq!(
+            dut.data.pulse_utc(Some(18)).unwrap(),
+            Utc.ymd(2020, 11, 4).and_hms_micro(13, 57, 24, 500)
+        );
+    }
+
+    #[test]
+    fn utc_time_base() {
+        let mut dut = UbxTimTp::create();
+        dut.data.tow_ms = 309_444_000;
+        dut.data.week = 2130;
+        dut.data.flags = FLAG_TIME_BASE_UTC | FLAG_UTC_AVAILABLE;
+        assert_eq!(
+            dut.data.pulse_utc(None).unwrap(),
+            Utc.ymd(2020, 11, 4).and_hms(13, 57, 24)
+        );
+
+        dut.data.flags = FLAG_TIME_BASE_UTC | FLAG_QERR_INVALID;
+        assert!(dut.data.pulse_utc(None).is_none());
+        assert!(dut.data.q_err().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        const DATA: [u8; 15] = [0; 15];
+        let mut dut = UbxTimTp::create();
+        dut.from_bin(&DATA);
+    }
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_repr::Serialize_repr;
 
 use crate::ubxlib::cid::UbxCID;
 use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
@@ -7,34 +7,44 @@ use crate::ubxlib::frame::{UbxFramePoll, UbxFrameWithData};
 const CLS: u8 = 0x09;
 const ID: u8 = 0x14;
 
-#[derive(Serialize_repr, Debug)]
+#[derive(Default, Serialize_repr, Debug)]
 #[repr(u8)]
 pub enum Command {
+    #[default]
     Backup = 0x00,
     Clear = 0x01,
 }
 
-impl Default for Command {
-    fn default() -> Self {
-        Command::Backup
-    }
-}
-
-#[derive(Deserialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+// Restore state reported on poll (cmd 0x03)
+#[derive(Default, PartialEq, Debug, Clone, Copy)]
 pub enum Response {
+    #[default]
     Unknown = 0,
     RestoreFailed = 1,
     Restored = 2,
     NotRestoredNoBackup = 3,
 }
 
-impl Default for Response {
-    fn default() -> Self {
-        Response::Unknown
+impl Response {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Response::RestoreFailed,
+            2 => Response::Restored,
+            3 => Response::NotRestoredNoBackup,
+            _ => Response::Unknown,
+        }
     }
 }
 
+// All messages output by the receiver
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SosMessage {
+    BackupCreated,    // cmd 0x02, backup command acknowledged
+    BackupNotCreated, // cmd 0x02, backup command not acknowledged
+    Restore(Response),
+    Unknown(u8),
+}
+
 pub struct UbxUpdSosPoll {}
 
 impl UbxUpdSosPoll {
@@ -43,14 +53,25 @@ impl UbxUpdSosPoll {
     }
 }
 
-#[derive(Default, Debug, Deserialize)]
+#[derive(Default, Debug, Serialize, Deserialize)]
 pub struct DataUpdSosResponse {
-    pub cmd: u8, // shall be 0x02
+    pub cmd: u8, // 0x02: backup created, 0x03: system restored
     pub res1: [u8; 3],
-    pub response: Response,
+    pub response: u8,
     pub res2: [u8; 3],
 }
 
+impl DataUpdSosResponse {
+    pub fn message(&self) -> SosMessage {
+        match (self.cmd, self.response) {
+            (0x02, 1) => SosMessage::BackupCreated,
+            (0x02, _) => SosMessage::BackupNotCreated,
+            (0x03, response) => SosMessage::Restore(Response::from_u8(response)),
+            (cmd, _) => SosMessage::Unknown(cmd),
+        }
+    }
+}
+
 pub struct UbxUpdSos {}
 
 impl UbxUpdSos {
@@ -135,6 +156,34 @@ mod response {
         dut.from_bin(&DATA);
 
         assert_eq!(dut.data.cmd, 0x03);
-        assert_eq!(dut.data.response, Response::Restored);
+        assert_eq!(dut.data.message(), SosMessage::Restore(Response::Restored));
+    }
+
+    #[test]
+    fn restore_states() {
+        let mut dut = UbxUpdSos::create();
+        for (code, response) in [
+            (0, Response::Unknown),
+            (1, Response::RestoreFailed),
+            (3, Response::NotRestoredNoBackup),
+        ]
+        .iter()
+        {
+            dut.from_bin(&[0x03, 0, 0, 0, *code, 0, 0, 0]);
+            assert_eq!(dut.data.message(), SosMessage::Restore(*response));
+        }
+    }
+
+    #[test]
+    fn backup_created() {
+        let mut dut = UbxUpdSos::create();
+        dut.from_bin(&[0x02, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(dut.data.message(), SosMessage::BackupCreated);
+
+        dut.from_bin(&[0x02, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(dut.data.message(), SosMessage::BackupNotCreated);
+
+        dut.from_bin(&[0x07, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(dut.data.message(), SosMessage::Unknown(0x07));
     }
 }